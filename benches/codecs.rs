@@ -0,0 +1,219 @@
+//! Throughput of every codec, for data sizes from 16 bytes to 16 MiB.
+//!
+//! Encoders are measured on random data. Decoders are measured on valid
+//! input, on input with a line break every 76 characters that must be
+//! ignored, and on input whose last character is invalid, so that the whole
+//! input is processed before the error is reported.
+//!
+//! Throughput is given in MB/s of binary data, and in cycles per byte of
+//! binary data. Cycles are estimated from the time and the CPU frequency,
+//! which is read from the `CT_CODECS_BENCH_GHZ` environment variable, or from
+//! `/proc/cpuinfo` on Linux. Frequency scaling makes that estimate rough;
+//! fixing the CPU frequency gives more stable numbers.
+//!
+//! Run with:
+//!
+//! ```text
+//! cargo bench --bench codecs [-- <filter>]
+//! ```
+//!
+//! Only the benchmarks whose name contains `<filter>` are run. The time spent
+//! on every measurement, in milliseconds, can be changed with the
+//! `CT_CODECS_BENCH_MS` environment variable.
+
+// `black_box()` needs a more recent Rust version than the library.
+#![allow(clippy::incompatible_msrv)]
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use common::Rng;
+use ct_codecs::*;
+
+const SIZES: [usize; 7] = [
+    16,
+    256,
+    4 * 1024,
+    64 * 1024,
+    256 * 1024,
+    1024 * 1024,
+    16 * 1024 * 1024,
+];
+
+const DEFAULT_MS: u64 = 200;
+
+// Measurements are split into batches, and the fastest batch is kept.
+const BATCHES: u32 = 5;
+
+const LINE_LEN: usize = 76;
+
+struct Bench {
+    filter: Option<String>,
+    duration: Duration,
+    ghz: Option<f64>,
+    bin: Vec<u8>,
+}
+
+impl Bench {
+    fn new() -> Self {
+        // `cargo bench` passes `--bench`; other flags are ignored as well.
+        let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+        let ms = std::env::var("CT_CODECS_BENCH_MS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(DEFAULT_MS);
+        let mut bin = vec![0u8; SIZES[SIZES.len() - 1]];
+        Rng(0x243f_6a88_85a3_08d3).fill(&mut bin);
+        Bench {
+            filter,
+            duration: Duration::from_millis(ms),
+            ghz: cpu_ghz(),
+            bin,
+        }
+    }
+
+    /// Returns the time of a single call to `f`, in seconds.
+    fn measure<F: FnMut()>(&self, mut f: F) -> f64 {
+        f();
+        let batch_duration = self.duration / BATCHES;
+        let mut iterations = 1u32;
+        loop {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                f();
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= batch_duration / 4 || iterations >= 1 << 24 {
+                let scale = batch_duration.as_secs_f64() / elapsed.as_secs_f64().max(1e-9);
+                iterations = ((iterations as f64 * scale) as u32).max(1);
+                break;
+            }
+            iterations *= 2;
+        }
+        (0..BATCHES)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..iterations {
+                    f();
+                }
+                start.elapsed().as_secs_f64() / iterations as f64
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn run<F: FnMut()>(&self, name: &str, size: usize, f: F) {
+        if let Some(filter) = &self.filter {
+            if !name.contains(filter.as_str()) {
+                return;
+            }
+        }
+        let secs = self.measure(f);
+        let mb_per_sec = size as f64 / secs / 1e6;
+        let cycles_per_byte = match self.ghz {
+            Some(ghz) => format!("{:10.2}", secs * ghz * 1e9 / size as f64),
+            None => format!("{:>10}", "-"),
+        };
+        println!(
+            "{:<44} {:>9} {:>10.1} {}",
+            name,
+            format_size(size),
+            mb_per_sec,
+            cycles_per_byte
+        );
+    }
+
+    fn encoder<E: Encoder>(&self, name: &str) {
+        for &size in &SIZES {
+            let bin = &self.bin[..size];
+            let mut encoded = vec![0u8; E::encoded_len(size).unwrap()];
+            self.run(&format!("{}::encode", name), size, || {
+                black_box(E::encode(&mut encoded, black_box(bin)).unwrap());
+            });
+        }
+    }
+
+    fn decoder<E: Encoder, D: Decoder>(&self, name: &str) {
+        for &size in &SIZES {
+            let encoded = E::encode_to_string(&self.bin[..size]).unwrap().into_bytes();
+            let mut bin = vec![0u8; size];
+
+            self.run(&format!("{}::decode", name), size, || {
+                black_box(D::decode(&mut bin, black_box(&encoded), None).unwrap());
+            });
+
+            let mut wrapped = Vec::with_capacity(encoded.len() + encoded.len() / LINE_LEN + 1);
+            for line in encoded.chunks(LINE_LEN) {
+                wrapped.extend_from_slice(line);
+                wrapped.push(b'\n');
+            }
+            self.run(&format!("{}::decode (ignore)", name), size, || {
+                black_box(D::decode(&mut bin, black_box(&wrapped), Some(b"\n")).unwrap());
+            });
+
+            let mut invalid = encoded.clone();
+            invalid.push(b'!');
+            self.run(&format!("{}::decode (invalid)", name), size, || {
+                black_box(D::decode(&mut bin, black_box(&invalid), None).unwrap_err());
+            });
+        }
+    }
+
+    fn codec<C: Encoder + Decoder>(&self, name: &str) {
+        self.encoder::<C>(name);
+        self.decoder::<C, C>(name);
+    }
+}
+
+fn format_size(size: usize) -> String {
+    match size {
+        size if size >= 1024 * 1024 => format!("{} MiB", size / (1024 * 1024)),
+        size if size >= 1024 => format!("{} KiB", size / 1024),
+        size => format!("{} B", size),
+    }
+}
+
+fn cpu_ghz() -> Option<f64> {
+    if let Some(ghz) = std::env::var("CT_CODECS_BENCH_GHZ")
+        .ok()
+        .and_then(|x| x.parse().ok())
+    {
+        return Some(ghz);
+    }
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    let mhz: f64 = cpuinfo
+        .lines()
+        .find(|line| line.starts_with("cpu MHz"))?
+        .split(':')
+        .nth(1)?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(mhz / 1000.0)
+}
+
+fn main() {
+    let bench = Bench::new();
+    match bench.ghz {
+        Some(ghz) => println!("CPU frequency: {:.2} GHz", ghz),
+        None => println!("CPU frequency unknown, set CT_CODECS_BENCH_GHZ to get cycles/byte"),
+    }
+    println!(
+        "{:<44} {:>9} {:>10} {:>10}",
+        "benchmark", "size", "MB/s", "cycles/B"
+    );
+
+    bench.codec::<Base64>("Base64");
+    bench.codec::<Base64NoPadding>("Base64NoPadding");
+    bench.codec::<Base64UrlSafe>("Base64UrlSafe");
+    bench.codec::<Base64UrlSafeNoPadding>("Base64UrlSafeNoPadding");
+    bench.decoder::<Base64, Base64Mixed>("Base64Mixed");
+    bench.decoder::<Base64NoPadding, Base64MixedNoPadding>("Base64MixedNoPadding");
+    bench.codec::<Base32>("Base32");
+    bench.codec::<Base32NoPadding>("Base32NoPadding");
+    bench.codec::<Base32Hex>("Base32Hex");
+    bench.codec::<Base32HexNoPadding>("Base32HexNoPadding");
+    bench.codec::<Hex>("Hex");
+}
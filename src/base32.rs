@@ -1,5 +1,8 @@
+use crate::buffer::*;
+use crate::ct::*;
 use crate::error::*;
-use crate::{Decoder, Encoder};
+use crate::literal::impl_decode_array;
+use crate::{Decoder, DecoderExt, Encoder};
 
 struct Base32Impl;
 
@@ -18,64 +21,35 @@ enum VariantMask {
 
 impl Base32Impl {
     #[inline]
-    fn is_no_padding(variant: Base32Variant) -> bool {
+    const fn is_no_padding(variant: Base32Variant) -> bool {
         (variant as u16 & VariantMask::NoPadding as u16) != 0
     }
 
     #[inline]
-    fn is_hex(variant: Base32Variant) -> bool {
+    const fn is_hex(variant: Base32Variant) -> bool {
         (variant as u16 & VariantMask::Hex as u16) != 0
     }
 
     #[inline]
-    fn _eq(x: u8, y: u8) -> u8 {
-        !(((0u16.wrapping_sub((x as u16) ^ (y as u16))) >> 8) as u8)
+    const fn b32_byte_to_char(x: Secret) -> Secret {
+        or(_map_range(x, 0, 25, b'A'), _map_range(x, 26, 31, b'2'))
     }
 
     #[inline]
-    fn _gt(x: u8, y: u8) -> u8 {
-        (((y as u16).wrapping_sub(x as u16)) >> 8) as u8
+    const fn b32_char_to_byte(c: Secret) -> Secret {
+        let x = or(_map_range(c, b'A', b'Z', 0), _map_range(c, b'2', b'7', 26));
+        or(x, and(_eq(x, 0), not(_eq(c, b'A'))))
     }
 
     #[inline]
-    fn _ge(x: u8, y: u8) -> u8 {
-        !Self::_gt(y, x)
+    const fn b32_hex_byte_to_char(x: Secret) -> Secret {
+        or(_map_range(x, 0, 9, b'0'), _map_range(x, 10, 31, b'A'))
     }
 
     #[inline]
-    fn _lt(x: u8, y: u8) -> u8 {
-        Self::_gt(y, x)
-    }
-
-    #[inline]
-    fn _le(x: u8, y: u8) -> u8 {
-        Self::_ge(y, x)
-    }
-
-    #[inline]
-    fn b32_byte_to_char(x: u8) -> u8 {
-        (Self::_lt(x, 26) & (x.wrapping_add(b'A')))
-            | (Self::_ge(x, 26) & Self::_lt(x, 32) & (x.wrapping_add(b'2'.wrapping_sub(26))))
-    }
-
-    #[inline]
-    fn b32_char_to_byte(c: u8) -> u8 {
-        let x = (Self::_ge(c, b'A') & Self::_le(c, b'Z') & (c.wrapping_sub(b'A')))
-            | (Self::_ge(c, b'2') & Self::_le(c, b'7') & (c.wrapping_sub(b'2').wrapping_add(26)));
-        x | (Self::_eq(x, 0) & (Self::_eq(c, b'A') ^ 0xff))
-    }
-
-    #[inline]
-    fn b32_hex_byte_to_char(x: u8) -> u8 {
-        (Self::_lt(x, 10) & (x.wrapping_add(b'0')))
-            | (Self::_ge(x, 10) & Self::_lt(x, 32) & (x.wrapping_add(b'A'.wrapping_sub(10))))
-    }
-
-    #[inline]
-    fn b32_hex_char_to_byte(c: u8) -> u8 {
-        let x = (Self::_ge(c, b'0') & Self::_le(c, b'9') & (c.wrapping_sub(b'0')))
-            | (Self::_ge(c, b'A') & Self::_le(c, b'V') & (c.wrapping_sub(b'A').wrapping_add(10)));
-        x | (Self::_eq(x, 0) & ((Self::_eq(c, b'0') | Self::_eq(c, b'A')) ^ 0xff))
+    const fn b32_hex_char_to_byte(c: Secret) -> Secret {
+        let x = or(_map_range(c, b'0', b'9', 0), _map_range(c, b'A', b'V', 10));
+        or(x, and(_eq(x, 0), not(or(_eq(c, b'0'), _eq(c, b'A')))))
     }
 
     #[inline]
@@ -95,6 +69,13 @@ impl Base32Impl {
         Ok(b32_len)
     }
 
+    // Every group of 8 characters decodes to 5 bytes, and an incomplete
+    // group to at most 4 bytes.
+    #[inline]
+    fn max_decoded_len(b32_len: usize) -> usize {
+        b32_len / 8 * 5 + (b32_len % 8) * 5 / 8
+    }
+
     #[allow(clippy::manual_div_ceil)]
     pub fn encode<'t>(
         b32: &'t mut [u8],
@@ -116,12 +97,16 @@ impl Base32Impl {
                 acc_len += 8;
                 while acc_len >= 5 {
                     acc_len -= 5;
-                    b32[b32_pos] = Self::b32_hex_byte_to_char(((acc >> acc_len) & 0x1f) as u8);
+                    b32[b32_pos] =
+                        Self::b32_hex_byte_to_char(Secret::new(((acc >> acc_len) & 0x1f) as u8))
+                            .expose();
                     b32_pos += 1;
                 }
             }
             if acc_len > 0 {
-                b32[b32_pos] = Self::b32_hex_byte_to_char(((acc << (5 - acc_len)) & 0x1f) as u8);
+                b32[b32_pos] =
+                    Self::b32_hex_byte_to_char(Secret::new(((acc << (5 - acc_len)) & 0x1f) as u8))
+                        .expose();
                 b32_pos += 1;
             }
         } else {
@@ -130,12 +115,16 @@ impl Base32Impl {
                 acc_len += 8;
                 while acc_len >= 5 {
                     acc_len -= 5;
-                    b32[b32_pos] = Self::b32_byte_to_char(((acc >> acc_len) & 0x1f) as u8);
+                    b32[b32_pos] =
+                        Self::b32_byte_to_char(Secret::new(((acc >> acc_len) & 0x1f) as u8))
+                            .expose();
                     b32_pos += 1;
                 }
             }
             if acc_len > 0 {
-                b32[b32_pos] = Self::b32_byte_to_char(((acc << (5 - acc_len)) & 0x1f) as u8);
+                b32[b32_pos] =
+                    Self::b32_byte_to_char(Secret::new(((acc << (5 - acc_len)) & 0x1f) as u8))
+                        .expose();
                 b32_pos += 1;
             }
         }
@@ -177,19 +166,107 @@ impl Base32Impl {
         ignore: Option<&[u8]>,
         variant: Base32Variant,
     ) -> Result<&'t [u8], Error> {
-        let bin_maxlen = bin.len();
+        let buffers = DecodeBuffers::Split {
+            input: b32,
+            output: Some(&mut *bin),
+        };
+        let bin_len = Self::decode_raw(buffers, ignore, variant)?;
+        Ok(&bin[..bin_len])
+    }
+
+    pub fn decode_in_place<'t>(
+        buf: &'t mut [u8],
+        ignore: Option<&[u8]>,
+        variant: Base32Variant,
+    ) -> Result<&'t [u8], Error> {
+        let bin_len = Self::decode_raw(DecodeBuffers::InPlace(&mut *buf), ignore, variant)?;
+        Ok(&buf[..bin_len])
+    }
+
+    pub fn validate(
+        b32: &[u8],
+        ignore: Option<&[u8]>,
+        variant: Base32Variant,
+    ) -> Result<usize, Error> {
+        let buffers = DecodeBuffers::Split {
+            input: b32,
+            output: None,
+        };
+        Self::decode_raw(buffers, ignore, variant)
+    }
+
+    // Strict decoder usable in constant expressions. Characters cannot be
+    // ignored, and the decoded data must be exactly `N` bytes long.
+    const fn decode_array<const N: usize>(
+        b32: &[u8],
+        variant: Base32Variant,
+    ) -> Result<[u8; N], Error> {
+        let is_hex = Self::is_hex(variant);
+        let mut bin = [0u8; N];
+        let mut acc = 0u16;
+        let mut acc_len = 0usize;
+        let mut bin_pos = 0usize;
+        let mut b32_pos = 0usize;
+        while b32_pos < b32.len() {
+            let c = secret(b32[b32_pos]);
+            let d = if is_hex {
+                expose(Self::b32_hex_char_to_byte(c))
+            } else {
+                expose(Self::b32_char_to_byte(c))
+            };
+            if d == 0xff {
+                break;
+            }
+            acc = (acc << 5) + d as u16;
+            acc_len += 5;
+            if acc_len >= 8 {
+                acc_len -= 8;
+                if bin_pos >= N {
+                    return Err(Error::Overflow);
+                }
+                bin[bin_pos] = (acc >> acc_len) as u8;
+                bin_pos += 1;
+            }
+            b32_pos += 1;
+        }
+        if acc_len >= 5 || (acc & ((1u16 << acc_len).wrapping_sub(1))) != 0 {
+            return Err(Error::InvalidInput);
+        }
+        if !Self::is_no_padding(variant) {
+            let mut padding_len = [0, 3, 6, 1, 4][acc_len];
+            while padding_len > 0 {
+                if b32_pos >= b32.len() || b32[b32_pos] != b'=' {
+                    return Err(Error::InvalidInput);
+                }
+                padding_len -= 1;
+                b32_pos += 1;
+            }
+        }
+        if b32_pos != b32.len() || bin_pos != N {
+            return Err(Error::InvalidInput);
+        }
+        Ok(bin)
+    }
+
+    fn decode_raw(
+        mut buffers: DecodeBuffers<'_>,
+        ignore: Option<&[u8]>,
+        variant: Base32Variant,
+    ) -> Result<usize, Error> {
         let is_hex = Self::is_hex(variant);
         let is_no_padding = Self::is_no_padding(variant);
         let mut acc = 0u16;
         let mut acc_len = 0usize;
         let mut bin_pos = 0usize;
         let mut premature_end = None;
-        for (b32_pos, &c) in b32.iter().enumerate() {
+        for b32_pos in 0..buffers.input_len() {
+            let c = buffers.input(b32_pos);
             let d = if is_hex {
-                Self::b32_hex_char_to_byte(c)
+                Self::b32_hex_char_to_byte(Secret::new(c))
             } else {
-                Self::b32_char_to_byte(c)
-            };
+                Self::b32_char_to_byte(Secret::new(c))
+            }
+            .expose();
             if d == 0xff {
                 match ignore {
                     Some(ignore) if ignore.contains(&c) => continue,
@@ -203,10 +280,7 @@ impl Base32Impl {
             acc_len += 5;
             if acc_len >= 8 {
                 acc_len -= 8;
-                if bin_pos >= bin_maxlen {
-                    return Err(Error::Overflow);
-                }
-                bin[bin_pos] = (acc >> acc_len) as u8;
+                buffers.write(bin_pos, (acc >> acc_len) as u8)?;
                 bin_pos += 1;
             }
         }
@@ -216,9 +290,9 @@ impl Base32Impl {
         let padding_len = [0, 3, 6, 1, 4][acc_len];
         if let Some(premature_end) = premature_end {
             let remaining = if !is_no_padding {
-                Self::skip_padding(&b32[premature_end..], padding_len, ignore)?
+                Self::skip_padding(buffers.input_from(premature_end), padding_len, ignore)?
             } else {
-                &b32[premature_end..]
+                buffers.input_from(premature_end)
             };
             match ignore {
                 None => {
@@ -237,7 +311,77 @@ impl Base32Impl {
         } else if !is_no_padding && padding_len != 0 {
             return Err(Error::InvalidInput);
         }
-        Ok(&bin[..bin_pos])
+        Ok(bin_pos)
+    }
+
+    pub fn diagnose<'t>(
+        bin: &'t mut [u8],
+        b32: &[u8],
+        ignore: Option<&[u8]>,
+        variant: Base32Variant,
+    ) -> Result<&'t [u8], Diagnostic> {
+        match Self::validate(b32, ignore, variant) {
+            Ok(bin_len) if bin_len > bin.len() => Err(Diagnostic::buffer_too_small(bin_len)),
+            Ok(bin_len) => Self::decode(bin, b32, ignore, variant)
+                .map_err(|_| Diagnostic::buffer_too_small(bin_len)),
+            Err(_) => Err(Self::locate_error(b32, ignore, variant)),
+        }
+    }
+
+    // Not constant-time: only used to explain why an input was rejected.
+    fn locate_error(b32: &[u8], ignore: Option<&[u8]>, variant: Base32Variant) -> Diagnostic {
+        let is_ignored = |c: u8| matches!(ignore, Some(ignore) if ignore.contains(&c));
+        let is_hex = Self::is_hex(variant);
+        let b32_len = b32.len();
+        let mut symbols = 0usize;
+        let mut last = (0usize, 0u8);
+        let mut end = b32_len;
+        for (b32_pos, &c) in b32.iter().enumerate() {
+            let d = if is_hex {
+                Self::b32_hex_char_to_byte(Secret::new(c))
+            } else {
+                Self::b32_char_to_byte(Secret::new(c))
+            }
+            .expose();
+            if d != 0xff {
+                symbols += 1;
+                last = (b32_pos, d);
+            } else if !is_ignored(c) {
+                end = b32_pos;
+                break;
+            }
+        }
+        if end < b32_len && b32[end] != b'=' {
+            return Diagnostic::at(ErrorKind::InvalidCharacter, end);
+        }
+        let remainder = symbols % 8;
+        if remainder == 1 || remainder == 3 || remainder == 6 {
+            return Diagnostic::at(ErrorKind::InvalidLength, last.0);
+        }
+        if last.1 & ((1u8 << (remainder * 5 % 8)) - 1) != 0 {
+            return Diagnostic::at(ErrorKind::NonCanonical, last.0);
+        }
+        let mut padding_len = if remainder == 0 { 0 } else { 8 - remainder };
+        if Self::is_no_padding(variant) {
+            padding_len = 0;
+        }
+        let mut b32_pos = end;
+        while padding_len > 0 {
+            if b32_pos >= b32_len {
+                return Diagnostic::at(ErrorKind::InvalidPadding, b32_len);
+            }
+            let c = b32[b32_pos];
+            if c == b'=' {
+                padding_len -= 1;
+            } else if !is_ignored(c) {
+                return Diagnostic::at(ErrorKind::InvalidPadding, b32_pos);
+            }
+            b32_pos += 1;
+        }
+        match b32[b32_pos..].iter().position(|&c| !is_ignored(c)) {
+            Some(offset) => Diagnostic::at(ErrorKind::InvalidPadding, b32_pos + offset),
+            None => Diagnostic::at(ErrorKind::InvalidPadding, b32_len),
+        }
     }
 }
 
@@ -349,6 +493,13 @@ pub struct Base32Hex;
 /// ```
 pub struct Base32HexNoPadding;
 
+impl_decode_array!(
+    Base32,
+    "base32!",
+    Base32Impl::decode_array,
+    Base32Variant::Standard
+);
+
 impl Encoder for Base32 {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -362,6 +513,11 @@ impl Encoder for Base32 {
 }
 
 impl Decoder for Base32 {
+    #[inline]
+    fn max_decoded_len(b32_len: usize) -> usize {
+        Base32Impl::max_decoded_len(b32_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -372,6 +528,34 @@ impl Decoder for Base32 {
     }
 }
 
+impl DecoderExt for Base32 {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b32: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base32Impl::validate(b32.as_ref(), ignore, Base32Variant::Standard)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base32Impl::decode_in_place(buf, ignore, Base32Variant::Standard)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b32: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base32Impl::diagnose(bin, b32.as_ref(), ignore, Base32Variant::Standard)
+    }
+}
+
+impl_decode_array!(
+    Base32NoPadding,
+    "base32!",
+    Base32Impl::decode_array,
+    Base32Variant::StandardNoPadding
+);
+
 impl Encoder for Base32NoPadding {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -385,6 +569,11 @@ impl Encoder for Base32NoPadding {
 }
 
 impl Decoder for Base32NoPadding {
+    #[inline]
+    fn max_decoded_len(b32_len: usize) -> usize {
+        Base32Impl::max_decoded_len(b32_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -395,6 +584,34 @@ impl Decoder for Base32NoPadding {
     }
 }
 
+impl DecoderExt for Base32NoPadding {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b32: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base32Impl::validate(b32.as_ref(), ignore, Base32Variant::StandardNoPadding)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base32Impl::decode_in_place(buf, ignore, Base32Variant::StandardNoPadding)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b32: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base32Impl::diagnose(bin, b32.as_ref(), ignore, Base32Variant::StandardNoPadding)
+    }
+}
+
+impl_decode_array!(
+    Base32Hex,
+    "base32!",
+    Base32Impl::decode_array,
+    Base32Variant::Hex
+);
+
 impl Encoder for Base32Hex {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -408,6 +625,11 @@ impl Encoder for Base32Hex {
 }
 
 impl Decoder for Base32Hex {
+    #[inline]
+    fn max_decoded_len(b32_len: usize) -> usize {
+        Base32Impl::max_decoded_len(b32_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -418,6 +640,34 @@ impl Decoder for Base32Hex {
     }
 }
 
+impl DecoderExt for Base32Hex {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b32: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base32Impl::validate(b32.as_ref(), ignore, Base32Variant::Hex)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base32Impl::decode_in_place(buf, ignore, Base32Variant::Hex)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b32: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base32Impl::diagnose(bin, b32.as_ref(), ignore, Base32Variant::Hex)
+    }
+}
+
+impl_decode_array!(
+    Base32HexNoPadding,
+    "base32!",
+    Base32Impl::decode_array,
+    Base32Variant::HexNoPadding
+);
+
 impl Encoder for Base32HexNoPadding {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -431,6 +681,11 @@ impl Encoder for Base32HexNoPadding {
 }
 
 impl Decoder for Base32HexNoPadding {
+    #[inline]
+    fn max_decoded_len(b32_len: usize) -> usize {
+        Base32Impl::max_decoded_len(b32_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -441,6 +696,27 @@ impl Decoder for Base32HexNoPadding {
     }
 }
 
+impl DecoderExt for Base32HexNoPadding {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b32: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base32Impl::validate(b32.as_ref(), ignore, Base32Variant::HexNoPadding)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base32Impl::decode_in_place(buf, ignore, Base32Variant::HexNoPadding)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b32: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base32Impl::diagnose(bin, b32.as_ref(), ignore, Base32Variant::HexNoPadding)
+    }
+}
+
 #[cfg(feature = "std")]
 #[test]
 fn test_base32() {
@@ -631,3 +907,98 @@ fn test_base32_hex_rejects_lowercase() {
     assert!(Base32Hex::decode_to_vec("cpnmuoj1e8======", None).is_err());
     assert!(Base32HexNoPadding::decode_to_vec("cpnmuoj1e8", None).is_err());
 }
+
+#[test]
+fn test_base32_validate() {
+    assert_eq!(Base32::validate("MZXW6YTBOI======", None), Ok(6));
+    assert_eq!(
+        Base32::validate("MZXW6YTBOI", None),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(Base32NoPadding::validate("MZXW6YTBOI", None), Ok(6));
+    assert_eq!(Base32Hex::validate("CPNMUOJ1E8======", None), Ok(6));
+    assert_eq!(Base32::validate("MZ======", None), Err(Error::InvalidInput));
+    assert_eq!(
+        Base32HexNoPadding::validate("cpnmuoj1e8", None),
+        Err(Error::InvalidInput)
+    );
+}
+
+#[test]
+fn test_base32_diagnose() {
+    let mut bin = [0u8; 6];
+    let cases: &[(&str, ErrorKind, usize)] = &[
+        ("MZXW6YTBO1======", ErrorKind::InvalidCharacter, 9),
+        ("MZXW6YTBO=======", ErrorKind::InvalidLength, 8),
+        ("MZXW6YTBOJ======", ErrorKind::NonCanonical, 9),
+        ("MZXW6YTBOI", ErrorKind::InvalidPadding, 10),
+        ("MZXW6YTBOI=====", ErrorKind::InvalidPadding, 15),
+        ("MZXW6YTBOI=====A", ErrorKind::InvalidPadding, 15),
+        ("MZXW6YTB========", ErrorKind::InvalidPadding, 8),
+    ];
+    for &(b32, kind, offset) in cases {
+        assert_eq!(
+            Base32::diagnose(&mut bin, b32, None),
+            Err(Diagnostic {
+                kind,
+                offset: Some(offset)
+            })
+        );
+        assert!(Base32::validate(b32, None).is_err());
+    }
+    assert_eq!(
+        Base32::diagnose(&mut bin[..5], "MZXW6YTBOI======", None),
+        Err(Diagnostic {
+            kind: ErrorKind::BufferTooSmall { needed: 6 },
+            offset: None
+        })
+    );
+    assert_eq!(
+        Base32HexNoPadding::diagnose(&mut bin, "CPNMUOJ1E8", None),
+        Ok(&b"foobar"[..])
+    );
+}
+
+#[test]
+fn test_base32_in_place() {
+    use crate::EncoderExt;
+
+    let mut buf = [0u8; 16];
+    buf[..6].copy_from_slice(b"foobar");
+    assert_eq!(
+        Base32::encode_in_place(&mut buf, 6).unwrap(),
+        b"MZXW6YTBOI======"
+    );
+    assert_eq!(Base32::decode_in_place(&mut buf, None).unwrap(), b"foobar");
+
+    let mut buf = *b"CPNM UOJ1 E8";
+    let decoded = Base32HexNoPadding::decode_in_place(&mut buf, Some(b" ")).unwrap();
+    assert_eq!(decoded, b"foobar");
+
+    let mut buf = [0u8; 10];
+    buf[..6].copy_from_slice(b"foobar");
+    assert_eq!(Base32::encode_in_place(&mut buf, 6), Err(Error::Overflow));
+    assert_eq!(
+        Base32HexNoPadding::encode_in_place(&mut buf, 6).unwrap(),
+        b"CPNMUOJ1E8"
+    );
+}
+
+#[test]
+fn test_base32_mappers() {
+    for x in 0..32u8 {
+        let c = Base32Impl::b32_byte_to_char(Tainted::new(x));
+        assert_eq!(Base32Impl::b32_char_to_byte(c).expose(), x);
+        let c = Base32Impl::b32_hex_byte_to_char(Tainted::new(x));
+        assert_eq!(Base32Impl::b32_hex_char_to_byte(c).expose(), x);
+    }
+    let value = |alphabet: &[u8], c: u8| alphabet.iter().position(|&x| x == c);
+    for c in 0..=255u8 {
+        let d = Base32Impl::b32_char_to_byte(Tainted::new(c)).expose();
+        let expected = value(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567", c);
+        assert_eq!(d, expected.map_or(0xff, |x| x as u8));
+        let d = Base32Impl::b32_hex_char_to_byte(Tainted::new(c)).expose();
+        let expected = value(b"0123456789ABCDEFGHIJKLMNOPQRSTUV", c);
+        assert_eq!(d, expected.map_or(0xff, |x| x as u8));
+    }
+}
@@ -1,5 +1,8 @@
+use crate::buffer::*;
+use crate::ct::*;
 use crate::error::*;
-use crate::{Decoder, Encoder};
+use crate::literal::impl_decode_array;
+use crate::{Decoder, DecoderExt, Encoder};
 
 struct Base64Impl;
 
@@ -9,75 +12,71 @@ enum Base64Variant {
     OriginalNoPadding = 3,
     UrlSafe = 5,
     UrlSafeNoPadding = 7,
+    Mixed = 9,
+    MixedNoPadding = 11,
 }
 
 enum VariantMask {
     NoPadding = 2,
     UrlSafe = 4,
+    Mixed = 8,
 }
 
-impl Base64Impl {
-    #[inline]
-    fn _eq(x: u8, y: u8) -> u8 {
-        !(((0u16.wrapping_sub((x as u16) ^ (y as u16))) >> 8) as u8)
-    }
-
-    #[inline]
-    fn _gt(x: u8, y: u8) -> u8 {
-        (((y as u16).wrapping_sub(x as u16)) >> 8) as u8
-    }
-
-    #[inline]
-    fn _ge(x: u8, y: u8) -> u8 {
-        !Self::_gt(y, x)
-    }
-
-    #[inline]
-    fn _lt(x: u8, y: u8) -> u8 {
-        Self::_gt(y, x)
-    }
-
-    #[inline]
-    fn _le(x: u8, y: u8) -> u8 {
-        Self::_ge(y, x)
-    }
+/// Base64 alphabet used as the reference when checking whether an input
+/// decoded with a mixed-alphabet decoder was canonical.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Base64Alphabet {
+    /// The standard alphabet, using '+' and '/' for values 62 and 63.
+    Standard,
+    /// The URL-safe alphabet, using '-' and '_' for values 62 and 63.
+    UrlSafe,
+}
 
+impl Base64Impl {
     #[inline]
-    fn b64_byte_to_char(x: u8) -> u8 {
-        (Self::_lt(x, 26) & (x.wrapping_add(b'A')))
-            | (Self::_ge(x, 26) & Self::_lt(x, 52) & (x.wrapping_add(b'a'.wrapping_sub(26))))
-            | (Self::_ge(x, 52) & Self::_lt(x, 62) & (x.wrapping_add(b'0'.wrapping_sub(52))))
-            | (Self::_eq(x, 62) & b'+')
-            | (Self::_eq(x, 63) & b'/')
+    const fn b64_byte_to_char(x: Secret) -> Secret {
+        any([
+            _map_range(x, 0, 25, b'A'),
+            _map_range(x, 26, 51, b'a'),
+            _map_range(x, 52, 61, b'0'),
+            _mask(_eq(x, 62), b'+'),
+            _mask(_eq(x, 63), b'/'),
+        ])
     }
 
     #[inline]
-    fn b64_char_to_byte(c: u8) -> u8 {
-        let x = (Self::_ge(c, b'A') & Self::_le(c, b'Z') & (c.wrapping_sub(b'A')))
-            | (Self::_ge(c, b'a') & Self::_le(c, b'z') & (c.wrapping_sub(b'a'.wrapping_sub(26))))
-            | (Self::_ge(c, b'0') & Self::_le(c, b'9') & (c.wrapping_sub(b'0'.wrapping_sub(52))))
-            | (Self::_eq(c, b'+') & 62)
-            | (Self::_eq(c, b'/') & 63);
-        x | (Self::_eq(x, 0) & (Self::_eq(c, b'A') ^ 0xff))
+    const fn b64_char_to_byte(c: Secret) -> Secret {
+        let x = any([
+            _map_range(c, b'A', b'Z', 0),
+            _map_range(c, b'a', b'z', 26),
+            _map_range(c, b'0', b'9', 52),
+            _mask(_eq(c, b'+'), 62),
+            _mask(_eq(c, b'/'), 63),
+        ]);
+        or(x, and(_eq(x, 0), not(_eq(c, b'A'))))
     }
 
     #[inline]
-    fn b64_byte_to_urlsafe_char(x: u8) -> u8 {
-        (Self::_lt(x, 26) & (x.wrapping_add(b'A')))
-            | (Self::_ge(x, 26) & Self::_lt(x, 52) & (x.wrapping_add(b'a'.wrapping_sub(26))))
-            | (Self::_ge(x, 52) & Self::_lt(x, 62) & (x.wrapping_add(b'0'.wrapping_sub(52))))
-            | (Self::_eq(x, 62) & b'-')
-            | (Self::_eq(x, 63) & b'_')
+    const fn b64_byte_to_urlsafe_char(x: Secret) -> Secret {
+        any([
+            _map_range(x, 0, 25, b'A'),
+            _map_range(x, 26, 51, b'a'),
+            _map_range(x, 52, 61, b'0'),
+            _mask(_eq(x, 62), b'-'),
+            _mask(_eq(x, 63), b'_'),
+        ])
     }
 
     #[inline]
-    fn b64_urlsafe_char_to_byte(c: u8) -> u8 {
-        let x = (Self::_ge(c, b'A') & Self::_le(c, b'Z') & (c.wrapping_sub(b'A')))
-            | (Self::_ge(c, b'a') & Self::_le(c, b'z') & (c.wrapping_sub(b'a'.wrapping_sub(26))))
-            | (Self::_ge(c, b'0') & Self::_le(c, b'9') & (c.wrapping_sub(b'0'.wrapping_sub(52))))
-            | (Self::_eq(c, b'-') & 62)
-            | (Self::_eq(c, b'_') & 63);
-        x | (Self::_eq(x, 0) & (Self::_eq(c, b'A') ^ 0xff))
+    const fn b64_urlsafe_char_to_byte(c: Secret) -> Secret {
+        let x = any([
+            _map_range(c, b'A', b'Z', 0),
+            _map_range(c, b'a', b'z', 26),
+            _map_range(c, b'0', b'9', 52),
+            _mask(_eq(c, b'-'), 62),
+            _mask(_eq(c, b'_'), 63),
+        ]);
+        or(x, and(_eq(x, 0), not(_eq(c, b'A'))))
     }
 
     #[inline]
@@ -97,6 +96,13 @@ impl Base64Impl {
         Ok(b64_len)
     }
 
+    // Every group of 4 characters decodes to 3 bytes, and an incomplete
+    // group to at most 2 bytes.
+    #[inline]
+    fn max_decoded_len(b64_len: usize) -> usize {
+        b64_len / 4 * 3 + (b64_len % 4) * 3 / 4
+    }
+
     pub fn encode<'t>(
         b64: &'t mut [u8],
         bin: &[u8],
@@ -127,13 +133,18 @@ impl Base64Impl {
                 acc_len += 8;
                 while acc_len >= 6 {
                     acc_len -= 6;
-                    b64[b64_pos] = Self::b64_byte_to_urlsafe_char(((acc >> acc_len) & 0x3f) as u8);
+                    b64[b64_pos] = Self::b64_byte_to_urlsafe_char(Secret::new(
+                        ((acc >> acc_len) & 0x3f) as u8,
+                    ))
+                    .expose();
                     b64_pos += 1;
                 }
             }
             if acc_len > 0 {
-                b64[b64_pos] =
-                    Self::b64_byte_to_urlsafe_char(((acc << (6 - acc_len)) & 0x3f) as u8);
+                b64[b64_pos] = Self::b64_byte_to_urlsafe_char(Secret::new(
+                    ((acc << (6 - acc_len)) & 0x3f) as u8,
+                ))
+                .expose();
                 b64_pos += 1;
             }
         } else {
@@ -142,12 +153,16 @@ impl Base64Impl {
                 acc_len += 8;
                 while acc_len >= 6 {
                     acc_len -= 6;
-                    b64[b64_pos] = Self::b64_byte_to_char(((acc >> acc_len) & 0x3f) as u8);
+                    b64[b64_pos] =
+                        Self::b64_byte_to_char(Secret::new(((acc >> acc_len) & 0x3f) as u8))
+                            .expose();
                     b64_pos += 1;
                 }
             }
             if acc_len > 0 {
-                b64[b64_pos] = Self::b64_byte_to_char(((acc << (6 - acc_len)) & 0x3f) as u8);
+                b64[b64_pos] =
+                    Self::b64_byte_to_char(Secret::new(((acc << (6 - acc_len)) & 0x3f) as u8))
+                        .expose();
                 b64_pos += 1;
             }
         }
@@ -189,18 +204,133 @@ impl Base64Impl {
         ignore: Option<&[u8]>,
         variant: Base64Variant,
     ) -> Result<&'t [u8], Error> {
-        let bin_maxlen = bin.len();
+        let buffers = DecodeBuffers::Split {
+            input: b64,
+            output: Some(&mut *bin),
+        };
+        let (bin_len, _) = Self::decode_raw(buffers, ignore, variant, Base64Alphabet::Standard)?;
+        Ok(&bin[..bin_len])
+    }
+
+    pub fn decode_checked<'t>(
+        bin: &'t mut [u8],
+        b64: &[u8],
+        ignore: Option<&[u8]>,
+        variant: Base64Variant,
+        alphabet: Base64Alphabet,
+    ) -> Result<(&'t [u8], bool), Error> {
+        let buffers = DecodeBuffers::Split {
+            input: b64,
+            output: Some(&mut *bin),
+        };
+        let (bin_len, canonical) = Self::decode_raw(buffers, ignore, variant, alphabet)?;
+        Ok((&bin[..bin_len], canonical))
+    }
+
+    pub fn decode_in_place<'t>(
+        buf: &'t mut [u8],
+        ignore: Option<&[u8]>,
+        variant: Base64Variant,
+    ) -> Result<&'t [u8], Error> {
+        let buffers = DecodeBuffers::InPlace(&mut *buf);
+        let (bin_len, _) = Self::decode_raw(buffers, ignore, variant, Base64Alphabet::Standard)?;
+        Ok(&buf[..bin_len])
+    }
+
+    pub fn validate(
+        b64: &[u8],
+        ignore: Option<&[u8]>,
+        variant: Base64Variant,
+    ) -> Result<usize, Error> {
+        let buffers = DecodeBuffers::Split {
+            input: b64,
+            output: None,
+        };
+        Self::decode_raw(buffers, ignore, variant, Base64Alphabet::Standard)
+            .map(|(bin_len, _)| bin_len)
+    }
+
+    // Strict decoder usable in constant expressions. Characters cannot be
+    // ignored, and the decoded data must be exactly `N` bytes long.
+    const fn decode_array<const N: usize>(
+        b64: &[u8],
+        variant: Base64Variant,
+    ) -> Result<[u8; N], Error> {
         let is_urlsafe = (variant as u16 & VariantMask::UrlSafe as u16) != 0;
+        let mut bin = [0u8; N];
         let mut acc = 0u16;
         let mut acc_len = 0usize;
         let mut bin_pos = 0usize;
-        let mut premature_end = None;
-        for (b64_pos, &c) in b64.iter().enumerate() {
+        let mut b64_pos = 0usize;
+        while b64_pos < b64.len() {
+            let c = secret(b64[b64_pos]);
             let d = if is_urlsafe {
-                Self::b64_urlsafe_char_to_byte(c)
+                expose(Self::b64_urlsafe_char_to_byte(c))
             } else {
-                Self::b64_char_to_byte(c)
+                expose(Self::b64_char_to_byte(c))
             };
+            if d == 0xff {
+                break;
+            }
+            acc = (acc << 6) + d as u16;
+            acc_len += 6;
+            if acc_len >= 8 {
+                acc_len -= 8;
+                if bin_pos >= N {
+                    return Err(Error::Overflow);
+                }
+                bin[bin_pos] = (acc >> acc_len) as u8;
+                bin_pos += 1;
+            }
+            b64_pos += 1;
+        }
+        if acc_len > 4 || (acc & ((1u16 << acc_len).wrapping_sub(1))) != 0 {
+            return Err(Error::InvalidInput);
+        }
+        if variant as u16 & VariantMask::NoPadding as u16 == 0 {
+            let mut padding_len = acc_len / 2;
+            while padding_len > 0 {
+                if b64_pos >= b64.len() || b64[b64_pos] != b'=' {
+                    return Err(Error::InvalidInput);
+                }
+                padding_len -= 1;
+                b64_pos += 1;
+            }
+        }
+        if b64_pos != b64.len() || bin_pos != N {
+            return Err(Error::InvalidInput);
+        }
+        Ok(bin)
+    }
+
+    fn decode_raw(
+        mut buffers: DecodeBuffers<'_>,
+        ignore: Option<&[u8]>,
+        variant: Base64Variant,
+        alphabet: Base64Alphabet,
+    ) -> Result<(usize, bool), Error> {
+        let is_urlsafe = (variant as u16 & VariantMask::UrlSafe as u16) != 0;
+        let is_mixed = (variant as u16 & VariantMask::Mixed as u16) != 0;
+        let check_urlsafe = alphabet == Base64Alphabet::UrlSafe;
+        let mut acc = 0u16;
+        let mut acc_len = 0usize;
+        let mut bin_pos = 0usize;
+        let mut non_canonical = Secret::new(0);
+        let mut premature_end = None;
+        for b64_pos in 0..buffers.input_len() {
+            let c = buffers.input(b64_pos);
+            let d = if is_mixed {
+                let d_std = Self::b64_char_to_byte(Secret::new(c));
+                let d_urlsafe = Self::b64_urlsafe_char_to_byte(Secret::new(c));
+                let d = d_std & d_urlsafe;
+                non_canonical |= d ^ if check_urlsafe { d_urlsafe } else { d_std };
+                d
+            } else if is_urlsafe {
+                Self::b64_urlsafe_char_to_byte(Secret::new(c))
+            } else {
+                Self::b64_char_to_byte(Secret::new(c))
+            }
+            .expose();
             if d == 0xff {
                 match ignore {
                     Some(ignore) if ignore.contains(&c) => continue,
@@ -214,10 +344,7 @@ impl Base64Impl {
             acc_len += 6;
             if acc_len >= 8 {
                 acc_len -= 8;
-                if bin_pos >= bin_maxlen {
-                    return Err(Error::Overflow);
-                }
-                bin[bin_pos] = (acc >> acc_len) as u8;
+                buffers.write(bin_pos, (acc >> acc_len) as u8)?;
                 bin_pos += 1;
             }
         }
@@ -227,9 +354,9 @@ impl Base64Impl {
         let padding_len = acc_len / 2;
         if let Some(premature_end) = premature_end {
             let remaining = if variant as u16 & VariantMask::NoPadding as u16 == 0 {
-                Self::skip_padding(&b64[premature_end..], padding_len, ignore)?
+                Self::skip_padding(buffers.input_from(premature_end), padding_len, ignore)?
             } else {
-                &b64[premature_end..]
+                buffers.input_from(premature_end)
             };
             match ignore {
                 None => {
@@ -248,7 +375,81 @@ impl Base64Impl {
         } else if variant as u16 & VariantMask::NoPadding as u16 == 0 && padding_len != 0 {
             return Err(Error::InvalidInput);
         }
-        Ok(&bin[..bin_pos])
+        Ok((bin_pos, non_canonical.expose() == 0))
+    }
+
+    pub fn diagnose<'t>(
+        bin: &'t mut [u8],
+        b64: &[u8],
+        ignore: Option<&[u8]>,
+        variant: Base64Variant,
+    ) -> Result<&'t [u8], Diagnostic> {
+        match Self::validate(b64, ignore, variant) {
+            Ok(bin_len) if bin_len > bin.len() => Err(Diagnostic::buffer_too_small(bin_len)),
+            Ok(bin_len) => Self::decode(bin, b64, ignore, variant)
+                .map_err(|_| Diagnostic::buffer_too_small(bin_len)),
+            Err(_) => Err(Self::locate_error(b64, ignore, variant)),
+        }
+    }
+
+    // Not constant-time: only used to explain why an input was rejected.
+    fn locate_error(b64: &[u8], ignore: Option<&[u8]>, variant: Base64Variant) -> Diagnostic {
+        let is_ignored = |c: u8| matches!(ignore, Some(ignore) if ignore.contains(&c));
+        let is_urlsafe = (variant as u16 & VariantMask::UrlSafe as u16) != 0;
+        let is_mixed = (variant as u16 & VariantMask::Mixed as u16) != 0;
+        let b64_len = b64.len();
+        let mut symbols = 0usize;
+        let mut last = (0usize, 0u8);
+        let mut end = b64_len;
+        for (b64_pos, &c) in b64.iter().enumerate() {
+            let c_secret = Secret::new(c);
+            let d = if is_mixed {
+                Self::b64_char_to_byte(c_secret) & Self::b64_urlsafe_char_to_byte(c_secret)
+            } else if is_urlsafe {
+                Self::b64_urlsafe_char_to_byte(c_secret)
+            } else {
+                Self::b64_char_to_byte(c_secret)
+            }
+            .expose();
+            if d != 0xff {
+                symbols += 1;
+                last = (b64_pos, d);
+            } else if !is_ignored(c) {
+                end = b64_pos;
+                break;
+            }
+        }
+        if end < b64_len && b64[end] != b'=' {
+            return Diagnostic::at(ErrorKind::InvalidCharacter, end);
+        }
+        let remainder = symbols % 4;
+        if remainder == 1 {
+            return Diagnostic::at(ErrorKind::InvalidLength, last.0);
+        }
+        if remainder != 0 && last.1 & ((1u8 << (2 * (4 - remainder))) - 1) != 0 {
+            return Diagnostic::at(ErrorKind::NonCanonical, last.0);
+        }
+        let mut padding_len = if remainder == 0 { 0 } else { 4 - remainder };
+        if variant as u16 & VariantMask::NoPadding as u16 != 0 {
+            padding_len = 0;
+        }
+        let mut b64_pos = end;
+        while padding_len > 0 {
+            if b64_pos >= b64_len {
+                return Diagnostic::at(ErrorKind::InvalidPadding, b64_len);
+            }
+            let c = b64[b64_pos];
+            if c == b'=' {
+                padding_len -= 1;
+            } else if !is_ignored(c) {
+                return Diagnostic::at(ErrorKind::InvalidPadding, b64_pos);
+            }
+            b64_pos += 1;
+        }
+        match b64[b64_pos..].iter().position(|&c| !is_ignored(c)) {
+            Some(offset) => Diagnostic::at(ErrorKind::InvalidPadding, b64_pos + offset),
+            None => Diagnostic::at(ErrorKind::InvalidPadding, b64_len),
+        }
     }
 }
 
@@ -368,6 +569,13 @@ pub struct Base64UrlSafe;
 /// ```
 pub struct Base64UrlSafeNoPadding;
 
+impl_decode_array!(
+    Base64,
+    "base64!",
+    Base64Impl::decode_array,
+    Base64Variant::Original
+);
+
 impl Encoder for Base64 {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -381,6 +589,11 @@ impl Encoder for Base64 {
 }
 
 impl Decoder for Base64 {
+    #[inline]
+    fn max_decoded_len(b64_len: usize) -> usize {
+        Base64Impl::max_decoded_len(b64_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -391,6 +604,34 @@ impl Decoder for Base64 {
     }
 }
 
+impl DecoderExt for Base64 {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b64: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base64Impl::validate(b64.as_ref(), ignore, Base64Variant::Original)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base64Impl::decode_in_place(buf, ignore, Base64Variant::Original)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base64Impl::diagnose(bin, b64.as_ref(), ignore, Base64Variant::Original)
+    }
+}
+
+impl_decode_array!(
+    Base64NoPadding,
+    "base64!",
+    Base64Impl::decode_array,
+    Base64Variant::OriginalNoPadding
+);
+
 impl Encoder for Base64NoPadding {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -404,6 +645,11 @@ impl Encoder for Base64NoPadding {
 }
 
 impl Decoder for Base64NoPadding {
+    #[inline]
+    fn max_decoded_len(b64_len: usize) -> usize {
+        Base64Impl::max_decoded_len(b64_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -414,6 +660,34 @@ impl Decoder for Base64NoPadding {
     }
 }
 
+impl DecoderExt for Base64NoPadding {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b64: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base64Impl::validate(b64.as_ref(), ignore, Base64Variant::OriginalNoPadding)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base64Impl::decode_in_place(buf, ignore, Base64Variant::OriginalNoPadding)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base64Impl::diagnose(bin, b64.as_ref(), ignore, Base64Variant::OriginalNoPadding)
+    }
+}
+
+impl_decode_array!(
+    Base64UrlSafe,
+    "base64!",
+    Base64Impl::decode_array,
+    Base64Variant::UrlSafe
+);
+
 impl Encoder for Base64UrlSafe {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -427,6 +701,11 @@ impl Encoder for Base64UrlSafe {
 }
 
 impl Decoder for Base64UrlSafe {
+    #[inline]
+    fn max_decoded_len(b64_len: usize) -> usize {
+        Base64Impl::max_decoded_len(b64_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -437,6 +716,34 @@ impl Decoder for Base64UrlSafe {
     }
 }
 
+impl DecoderExt for Base64UrlSafe {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b64: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base64Impl::validate(b64.as_ref(), ignore, Base64Variant::UrlSafe)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base64Impl::decode_in_place(buf, ignore, Base64Variant::UrlSafe)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base64Impl::diagnose(bin, b64.as_ref(), ignore, Base64Variant::UrlSafe)
+    }
+}
+
+impl_decode_array!(
+    Base64UrlSafeNoPadding,
+    "base64!",
+    Base64Impl::decode_array,
+    Base64Variant::UrlSafeNoPadding
+);
+
 impl Encoder for Base64UrlSafeNoPadding {
     #[inline]
     fn encoded_len(bin_len: usize) -> Result<usize, Error> {
@@ -450,6 +757,11 @@ impl Encoder for Base64UrlSafeNoPadding {
 }
 
 impl Decoder for Base64UrlSafeNoPadding {
+    #[inline]
+    fn max_decoded_len(b64_len: usize) -> usize {
+        Base64Impl::max_decoded_len(b64_len)
+    }
+
     #[inline]
     fn decode<'t, IN: AsRef<[u8]>>(
         bin: &'t mut [u8],
@@ -460,6 +772,185 @@ impl Decoder for Base64UrlSafeNoPadding {
     }
 }
 
+impl DecoderExt for Base64UrlSafeNoPadding {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b64: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base64Impl::validate(b64.as_ref(), ignore, Base64Variant::UrlSafeNoPadding)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base64Impl::decode_in_place(buf, ignore, Base64Variant::UrlSafeNoPadding)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base64Impl::diagnose(bin, b64.as_ref(), ignore, Base64Variant::UrlSafeNoPadding)
+    }
+}
+
+/// Base64 decoder accepting both the standard and the URL-safe alphabets, with padding.
+///
+/// Input may freely mix '+'/'/' and '-'/'_' for the values 62 and 63, which is
+/// useful when receiving data from producers that don't agree on an alphabet.
+/// The other strict checks (padding, non-canonical trailing bits) still apply.
+///
+/// This type only implements [`Decoder`]; use [`Base64`] or [`Base64UrlSafe`]
+/// to produce canonical output.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::{Base64Alphabet, Base64Mixed, Decoder};
+///
+/// fn example() -> Result<(), ct_codecs::Error> {
+///     let decoded = Base64Mixed::decode_to_vec("-+_/", None)?;
+///     assert_eq!(decoded, [251, 239, 255]);
+///
+///     let mut bin = [0u8; 4];
+///     let (decoded, canonical) =
+///         Base64Mixed::decode_with_alphabet_check(&mut bin, "----", None, Base64Alphabet::UrlSafe)?;
+///     assert_eq!(decoded, [251, 239, 190]);
+///     assert!(canonical);
+///     Ok(())
+/// }
+/// # example().unwrap();
+/// ```
+pub struct Base64Mixed;
+
+/// Base64 decoder accepting both the standard and the URL-safe alphabets, without padding.
+///
+/// This is the unpadded counterpart of [`Base64Mixed`].
+pub struct Base64MixedNoPadding;
+
+impl Base64Mixed {
+    /// Decodes mixed-alphabet Base64 data and reports whether it was canonical.
+    ///
+    /// The returned flag is `true` if every symbol of the input belongs to
+    /// `alphabet`, so that the input is exactly what the corresponding encoder
+    /// would have produced. The check runs in constant time, in the same pass
+    /// as decoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Mutable buffer to store the decoded output
+    /// * `b64` - Base64 input data to decode
+    /// * `ignore` - Optional set of characters to ignore during decoding
+    /// * `alphabet` - Alphabet the input is checked against
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((&[u8], bool))` - The decoded data, and whether the input was canonical
+    /// * `Err(Error::Overflow)` - If the output buffer is too small
+    /// * `Err(Error::InvalidInput)` - If the input is not valid Base64
+    pub fn decode_with_alphabet_check<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+        alphabet: Base64Alphabet,
+    ) -> Result<(&'t [u8], bool), Error> {
+        Base64Impl::decode_checked(bin, b64.as_ref(), ignore, Base64Variant::Mixed, alphabet)
+    }
+}
+
+impl Base64MixedNoPadding {
+    /// Decodes unpadded mixed-alphabet Base64 data and reports whether it was canonical.
+    ///
+    /// See [`Base64Mixed::decode_with_alphabet_check`].
+    pub fn decode_with_alphabet_check<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+        alphabet: Base64Alphabet,
+    ) -> Result<(&'t [u8], bool), Error> {
+        Base64Impl::decode_checked(
+            bin,
+            b64.as_ref(),
+            ignore,
+            Base64Variant::MixedNoPadding,
+            alphabet,
+        )
+    }
+}
+
+impl Decoder for Base64Mixed {
+    #[inline]
+    fn max_decoded_len(b64_len: usize) -> usize {
+        Base64Impl::max_decoded_len(b64_len)
+    }
+
+    #[inline]
+    fn decode<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Error> {
+        Base64Impl::decode(bin, b64.as_ref(), ignore, Base64Variant::Mixed)
+    }
+}
+
+impl DecoderExt for Base64Mixed {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b64: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base64Impl::validate(b64.as_ref(), ignore, Base64Variant::Mixed)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base64Impl::decode_in_place(buf, ignore, Base64Variant::Mixed)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base64Impl::diagnose(bin, b64.as_ref(), ignore, Base64Variant::Mixed)
+    }
+}
+
+impl Decoder for Base64MixedNoPadding {
+    #[inline]
+    fn max_decoded_len(b64_len: usize) -> usize {
+        Base64Impl::max_decoded_len(b64_len)
+    }
+
+    #[inline]
+    fn decode<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Error> {
+        Base64Impl::decode(bin, b64.as_ref(), ignore, Base64Variant::MixedNoPadding)
+    }
+}
+
+impl DecoderExt for Base64MixedNoPadding {
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(b64: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        Base64Impl::validate(b64.as_ref(), ignore, Base64Variant::MixedNoPadding)
+    }
+
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        Base64Impl::decode_in_place(buf, ignore, Base64Variant::MixedNoPadding)
+    }
+
+    #[inline]
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        b64: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
+        Base64Impl::diagnose(bin, b64.as_ref(), ignore, Base64Variant::MixedNoPadding)
+    }
+}
+
 #[cfg(feature = "std")]
 #[test]
 fn test_base64() {
@@ -519,3 +1010,200 @@ fn test_base64_no_padding_rejects_padding() {
     assert!(Base64NoPadding::decode_to_vec("AA==", None).is_err());
     assert!(Base64NoPadding::decode_to_vec("AAA=", None).is_err());
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn test_base64_mixed() {
+    let bin = [251u8, 239, 239, 255, 0];
+    let expected = Base64::decode_to_vec("++/v/wA=", None).unwrap();
+    assert_eq!(expected, bin);
+    for b64 in ["++/v/wA=", "--_v_wA=", "+-/v_wA="] {
+        assert_eq!(Base64Mixed::decode_to_vec(b64, None).unwrap(), bin);
+        assert_eq!(
+            Base64MixedNoPadding::decode_to_vec(&b64[..7], None).unwrap(),
+            bin
+        );
+        assert!(Base64MixedNoPadding::decode_to_vec(b64, None).is_err());
+    }
+    assert!(Base64Mixed::decode_to_vec("++/v/wA", None).is_err());
+    assert!(Base64Mixed::decode_to_vec("++/v/wB=", None).is_err());
+    assert!(Base64Mixed::decode_to_vec("++/v.wA=", None).is_err());
+}
+
+#[test]
+fn test_base64_mixed_alphabet_check() {
+    let mut bin = [0u8; 5];
+    let cases = [
+        ("++/v/wA=", true, false),
+        ("--_v_wA=", false, true),
+        ("+-/v_wA=", false, false),
+        ("AAAA", true, true),
+    ];
+    for &(b64, standard, urlsafe) in &cases {
+        let (_, canonical) =
+            Base64Mixed::decode_with_alphabet_check(&mut bin, b64, None, Base64Alphabet::Standard)
+                .unwrap();
+        assert_eq!(canonical, standard);
+        let (_, canonical) =
+            Base64Mixed::decode_with_alphabet_check(&mut bin, b64, None, Base64Alphabet::UrlSafe)
+                .unwrap();
+        assert_eq!(canonical, urlsafe);
+    }
+    let (decoded, canonical) = Base64MixedNoPadding::decode_with_alphabet_check(
+        &mut bin,
+        "-- _v",
+        Some(b" "),
+        Base64Alphabet::UrlSafe,
+    )
+    .unwrap();
+    assert_eq!(decoded, [251, 239, 239]);
+    assert!(canonical);
+}
+
+#[test]
+fn test_base64_validate() {
+    assert_eq!(Base64::validate("AQULDxODeg==", None), Ok(7));
+    assert_eq!(
+        Base64::validate("AQULDxODeg", None),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(Base64NoPadding::validate("AQULDxODeg", None), Ok(7));
+    assert_eq!(Base64::validate("AQUL DxOD eg==", Some(b" ")), Ok(7));
+    assert_eq!(Base64::validate("AB==", None), Err(Error::InvalidInput));
+    assert_eq!(Base64UrlSafeNoPadding::validate("----3g", None), Ok(4));
+    assert_eq!(
+        Base64UrlSafeNoPadding::validate("++++3g", None),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(Base64MixedNoPadding::validate("-+-+3g", None), Ok(4));
+}
+
+#[test]
+fn test_base64_diagnose() {
+    let mut bin = [0u8; 7];
+    let cases: &[(&str, ErrorKind, usize)] = &[
+        ("AQUL.xODeg==", ErrorKind::InvalidCharacter, 4),
+        ("AQULDxODe===", ErrorKind::InvalidLength, 8),
+        ("AQULDxODeh==", ErrorKind::NonCanonical, 9),
+        ("AQULDxODeg", ErrorKind::InvalidPadding, 10),
+        ("AQULDxODeg=", ErrorKind::InvalidPadding, 11),
+        ("AQULDxODeg=A", ErrorKind::InvalidPadding, 11),
+        ("AQULDxODeg===", ErrorKind::InvalidPadding, 12),
+        ("AQUL=xODeg==", ErrorKind::InvalidPadding, 4),
+    ];
+    for &(b64, kind, offset) in cases {
+        assert_eq!(
+            Base64::diagnose(&mut bin, b64, None),
+            Err(Diagnostic {
+                kind,
+                offset: Some(offset)
+            })
+        );
+        assert!(Base64::validate(b64, None).is_err());
+    }
+    assert_eq!(
+        Base64NoPadding::diagnose(&mut bin, "AQULDxODeg==", None),
+        Err(Diagnostic {
+            kind: ErrorKind::InvalidPadding,
+            offset: Some(10)
+        })
+    );
+    assert_eq!(
+        Base64::diagnose(&mut bin[..6], "AQULDxODeg==", None),
+        Err(Diagnostic {
+            kind: ErrorKind::BufferTooSmall { needed: 7 },
+            offset: None
+        })
+    );
+    assert_eq!(
+        Base64::diagnose(&mut bin, "AQUL DxOD eg==", Some(b" ")),
+        Ok(&[1u8, 5, 11, 15, 19, 131, 122][..])
+    );
+}
+
+#[test]
+fn test_base64_in_place() {
+    use crate::EncoderExt;
+
+    let bin = [1u8, 5, 11, 15, 19, 131, 122];
+    let mut buf = [0u8; 12];
+    buf[..7].copy_from_slice(&bin);
+    assert_eq!(
+        Base64::encode_in_place(&mut buf, 7).unwrap(),
+        b"AQULDxODeg=="
+    );
+    assert_eq!(Base64::decode_in_place(&mut buf, None).unwrap(), bin);
+
+    let mut buf = *b"AQUL DxOD eg";
+    let decoded = Base64NoPadding::decode_in_place(&mut buf, Some(b" ")).unwrap();
+    assert_eq!(decoded, bin);
+
+    let mut buf = [0u8; 11];
+    buf[..7].copy_from_slice(&bin);
+    assert_eq!(Base64::encode_in_place(&mut buf, 7), Err(Error::Overflow));
+    assert_eq!(
+        Base64NoPadding::encode_in_place(&mut buf, 7).unwrap(),
+        b"AQULDxODeg"
+    );
+    let mut buf = *b"AQULDxODeg=";
+    assert_eq!(
+        Base64::decode_in_place(&mut buf, None),
+        Err(Error::InvalidInput)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_base64_in_place_long() {
+    use crate::EncoderExt;
+
+    let bin: Vec<u8> = (0..500u32).map(|i| (i * 13 + 7) as u8).collect();
+    for len in [0, 1, 2, 89, 90, 91, 180, 181, 500] {
+        let expected = Base64UrlSafe::encode_to_string(&bin[..len]).unwrap();
+        let mut buf = vec![0u8; expected.len()];
+        buf[..len].copy_from_slice(&bin[..len]);
+        let encoded = Base64UrlSafe::encode_in_place(&mut buf, len).unwrap();
+        assert_eq!(encoded, expected.as_bytes());
+        let decoded = Base64UrlSafe::decode_in_place(&mut buf, None).unwrap();
+        assert_eq!(decoded, &bin[..len]);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_base64_display() {
+    use crate::EncoderExt;
+
+    let bin: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+    for len in [0, 1, 2, 89, 90, 91, 1000] {
+        let bin = &bin[..len];
+        assert_eq!(
+            Base64::display(bin).to_string(),
+            Base64::encode_to_string(bin).unwrap()
+        );
+        assert_eq!(
+            Base64UrlSafeNoPadding::display(bin).to_string(),
+            Base64UrlSafeNoPadding::encode_to_string(bin).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_base64_mappers() {
+    for x in 0..64u8 {
+        let c = Base64Impl::b64_byte_to_char(Tainted::new(x));
+        assert_eq!(Base64Impl::b64_char_to_byte(c).expose(), x);
+        let c = Base64Impl::b64_byte_to_urlsafe_char(Tainted::new(x));
+        assert_eq!(Base64Impl::b64_urlsafe_char_to_byte(c).expose(), x);
+    }
+    const DIGITS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let value = |alphabet: &[u8], c: u8| alphabet.iter().position(|&x| x == c);
+    for c in 0..=255u8 {
+        let d = Base64Impl::b64_char_to_byte(Tainted::new(c)).expose();
+        let expected = value(DIGITS, c).or(value(b"+/", c).map(|x| x + 62));
+        assert_eq!(d, expected.map_or(0xff, |x| x as u8));
+        let d = Base64Impl::b64_urlsafe_char_to_byte(Tainted::new(c)).expose();
+        let expected = value(DIGITS, c).or(value(b"-_", c).map(|x| x + 62));
+        assert_eq!(d, expected.map_or(0xff, |x| x as u8));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::error::*;
+
+/// Input and output of a decoding operation.
+///
+/// Decoded data is never longer than the encoded data it comes from, and is
+/// written at positions that have already been read. So the input can also
+/// be decoded over itself.
+pub(crate) enum DecodeBuffers<'a> {
+    /// Separate input and output buffers. Without an output buffer, the
+    /// input is only validated.
+    Split {
+        input: &'a [u8],
+        output: Option<&'a mut [u8]>,
+    },
+    /// The input is decoded in place.
+    InPlace(&'a mut [u8]),
+}
+
+impl DecodeBuffers<'_> {
+    #[inline]
+    pub fn input_len(&self) -> usize {
+        match self {
+            DecodeBuffers::Split { input, .. } => input.len(),
+            DecodeBuffers::InPlace(buf) => buf.len(),
+        }
+    }
+
+    #[inline]
+    pub fn input(&self, pos: usize) -> u8 {
+        match self {
+            DecodeBuffers::Split { input, .. } => input[pos],
+            DecodeBuffers::InPlace(buf) => buf[pos],
+        }
+    }
+
+    /// Returns the input from `pos`, which must not have been overwritten yet.
+    #[inline]
+    pub fn input_from(&self, pos: usize) -> &[u8] {
+        match self {
+            DecodeBuffers::Split { input, .. } => &input[pos..],
+            DecodeBuffers::InPlace(buf) => &buf[pos..],
+        }
+    }
+
+    #[inline]
+    pub fn write(&mut self, pos: usize, x: u8) -> Result<(), Error> {
+        match self {
+            DecodeBuffers::Split {
+                output: Some(output),
+                ..
+            } => {
+                if pos >= output.len() {
+                    return Err(Error::Overflow);
+                }
+                output[pos] = x;
+            }
+            DecodeBuffers::Split { output: None, .. } => {}
+            DecodeBuffers::InPlace(buf) => buf[pos] = x,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,682 @@
+//! Constant-time primitives on bytes.
+//!
+//! These are the building blocks of every codec of the crate, and can be used
+//! to write parsers with the same guarantees. They only use bitwise operations
+//! and wrapping arithmetic: no branches, no table lookups, and no comparison
+//! operators that the compiler could turn into branches.
+//!
+//! Conditions are represented as a [`Choice`], a byte mask that is either
+//! `0xff` (true) or `0x00` (false).
+//!
+//! # Examples
+//!
+//! ```
+//! use ct_codecs::ct::{self, Choice};
+//!
+//! // Maps a decimal digit to its value, and returns whether it was valid.
+//! fn digit_value(c: u8) -> (u8, Choice) {
+//!     (ct::ct_map_range(c, b'0', b'9', 0), ct::ct_in_range(c, b'0', b'9'))
+//! }
+//!
+//! let (value, valid) = digit_value(b'7');
+//! assert_eq!((value, bool::from(valid)), (7, true));
+//! let (_, valid) = digit_value(b'x');
+//! assert!(!bool::from(valid));
+//!
+//! let mut a = *b"left";
+//! let mut b = *b"rght";
+//! ct::ct_swap_if(ct::ct_eq_u8(value, 7), &mut a, &mut b);
+//! assert_eq!((&a, &b), (b"rght", b"left"));
+//! ```
+
+use core::ops::{BitAnd, BitOr, BitXor, Not};
+
+#[cfg(any(test, feature = "ct-taint"))]
+use core::ops::{BitAndAssign, BitOrAssign};
+
+// The primitives and the character mappers of the codecs work on `Secret`
+// bytes, that are `Tainted` in unit tests and with the `ct-taint` feature:
+// bytes that can't be branched on, compared or used as an index. They are
+// `const fn`, so that the decoders usable in constant expressions share them
+// with the runtime decoders.
+//
+// This only covers the computations on secret bytes. `expose()` returns a
+// plain byte, either to store it, or to check a validity mask: the decoders
+// branch on whether every character is part of the alphabet, to skip ignored
+// characters and find the end of the data. So the position of ignored and
+// invalid characters isn't secret, as documented for `decode_either`. The
+// integration tests in `tests/` run with `Tainted` bytes too when the
+// `ct-taint` feature is enabled.
+
+/// Byte type used by the codecs for secret data.
+///
+/// This is `u8`, except in unit tests and with the `ct-taint` feature, where
+/// it is [`Tainted`].
+#[cfg(not(any(test, feature = "ct-taint")))]
+pub(crate) type Secret = u8;
+
+#[cfg(any(test, feature = "ct-taint"))]
+pub(crate) type Secret = Tainted;
+
+/// Wraps a byte.
+#[cfg(not(any(test, feature = "ct-taint")))]
+#[inline]
+pub(crate) const fn secret(x: u8) -> Secret {
+    x
+}
+
+/// Wraps a byte.
+#[cfg(any(test, feature = "ct-taint"))]
+#[inline]
+pub(crate) const fn secret(x: u8) -> Secret {
+    Tainted(x)
+}
+
+/// Returns the wrapped byte, to store it or to check a validity mask.
+#[cfg(not(any(test, feature = "ct-taint")))]
+#[inline]
+pub(crate) const fn expose(x: Secret) -> u8 {
+    x
+}
+
+/// Returns the wrapped byte, to store it or to check a validity mask.
+#[cfg(any(test, feature = "ct-taint"))]
+#[inline]
+pub(crate) const fn expose(x: Secret) -> u8 {
+    x.0
+}
+
+/// Method syntax for the conversions of secret bytes, outside of constant expressions.
+pub(crate) trait SecretByte: Copy {
+    /// Wraps a byte.
+    fn new(x: u8) -> Self;
+
+    /// Returns the wrapped byte, to store it or to check a validity mask.
+    fn expose(self) -> u8;
+}
+
+impl SecretByte for Secret {
+    #[inline]
+    fn new(x: u8) -> Self {
+        secret(x)
+    }
+
+    #[inline]
+    fn expose(self) -> u8 {
+        expose(self)
+    }
+}
+
+// Operators can't be used on `Secret` in constant expressions.
+
+#[inline]
+pub(crate) const fn and(x: Secret, y: Secret) -> Secret {
+    secret(expose(x) & expose(y))
+}
+
+#[inline]
+pub(crate) const fn or(x: Secret, y: Secret) -> Secret {
+    secret(expose(x) | expose(y))
+}
+
+#[inline]
+const fn xor(x: Secret, y: Secret) -> Secret {
+    secret(expose(x) ^ expose(y))
+}
+
+#[inline]
+pub(crate) const fn not(x: Secret) -> Secret {
+    secret(!expose(x))
+}
+
+/// Returns the bitwise or of all the bytes of `xs`.
+#[inline]
+pub(crate) const fn any<const N: usize>(xs: [Secret; N]) -> Secret {
+    let mut acc = secret(0);
+    let mut i = 0;
+    while i < N {
+        acc = or(acc, xs[i]);
+        i += 1;
+    }
+    acc
+}
+
+/// Returns 0xff if `x < y`, 0 otherwise: the borrow out of `x - y`.
+#[inline]
+pub(crate) const fn borrow(x: Secret, y: Secret) -> Secret {
+    secret(((expose(x) as u16).wrapping_sub(expose(y) as u16) >> 8) as u8)
+}
+
+/// Returns 0xff if `x == y`, 0 otherwise.
+#[inline]
+pub(crate) const fn _eq(x: Secret, y: u8) -> Secret {
+    not(borrow(secret(0), xor(x, secret(y))))
+}
+
+/// Returns 0xff if `x > y`, 0 otherwise.
+#[inline]
+pub(crate) const fn _gt(x: Secret, y: u8) -> Secret {
+    borrow(secret(y), x)
+}
+
+/// Returns 0xff if `x < y`, 0 otherwise.
+#[inline]
+pub(crate) const fn _lt(x: Secret, y: u8) -> Secret {
+    borrow(x, secret(y))
+}
+
+/// Returns 0xff if `x >= y`, 0 otherwise.
+#[inline]
+pub(crate) const fn _ge(x: Secret, y: u8) -> Secret {
+    not(_lt(x, y))
+}
+
+/// Returns 0xff if `x <= y`, 0 otherwise.
+#[inline]
+pub(crate) const fn _le(x: Secret, y: u8) -> Secret {
+    not(_gt(x, y))
+}
+
+/// Returns 0xff if `lo <= x <= hi`, 0 otherwise.
+#[inline]
+pub(crate) const fn _in_range(x: Secret, lo: u8, hi: u8) -> Secret {
+    and(_ge(x, lo), _le(x, hi))
+}
+
+/// Returns `x - lo + base` if `lo <= x <= hi`, 0 otherwise.
+#[inline]
+pub(crate) const fn _map_range(x: Secret, lo: u8, hi: u8, base: u8) -> Secret {
+    and(
+        _in_range(x, lo, hi),
+        secret(expose(x).wrapping_add(base.wrapping_sub(lo))),
+    )
+}
+
+/// Returns `value` if `mask` is 0xff, 0 if it is 0.
+#[inline]
+pub(crate) const fn _mask(mask: Secret, value: u8) -> Secret {
+    and(mask, secret(value))
+}
+
+/// Returns `a` if `mask` is 0xff, `b` if it is 0.
+#[inline]
+pub(crate) const fn _select(mask: Secret, a: Secret, b: Secret) -> Secret {
+    xor(b, and(mask, xor(a, b)))
+}
+
+/// The result of a constant-time comparison.
+///
+/// A `Choice` is stored as a byte mask, `0xff` for true and `0x00` for false,
+/// that can be combined with the bitwise operators. Converting it to a `bool`
+/// is what reveals the condition, so it should only be done once the result
+/// can be made public.
+#[derive(Copy, Clone, Debug)]
+pub struct Choice(u8);
+
+impl Choice {
+    /// A condition that is true.
+    pub const TRUE: Choice = Choice(0xff);
+
+    /// A condition that is false.
+    pub const FALSE: Choice = Choice(0x00);
+
+    /// Creates a `Choice` from a boolean.
+    #[inline]
+    pub fn from_bool(b: bool) -> Self {
+        Choice((b as u8).wrapping_neg())
+    }
+
+    /// Returns the condition as a byte mask: `0xff` if true, `0x00` if false.
+    #[inline]
+    pub fn mask(self) -> u8 {
+        self.0
+    }
+
+    /// Creates a `Choice` from a secret byte mask.
+    #[inline]
+    pub(crate) const fn from_secret(mask: Secret) -> Self {
+        Choice(expose(mask))
+    }
+}
+
+impl BitAnd for Choice {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, y: Self) -> Self {
+        Choice(self.0 & y.0)
+    }
+}
+
+impl BitOr for Choice {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, y: Self) -> Self {
+        Choice(self.0 | y.0)
+    }
+}
+
+impl BitXor for Choice {
+    type Output = Self;
+
+    #[inline]
+    fn bitxor(self, y: Self) -> Self {
+        Choice(self.0 ^ y.0)
+    }
+}
+
+impl Not for Choice {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        Choice(!self.0)
+    }
+}
+
+impl From<Choice> for bool {
+    #[inline]
+    fn from(choice: Choice) -> bool {
+        choice.0 != 0
+    }
+}
+
+/// Checks whether two bytes are equal, in constant time.
+#[inline]
+pub fn ct_eq_u8(x: u8, y: u8) -> Choice {
+    Choice(expose(_eq(secret(x), y)))
+}
+
+/// Checks whether `x < y`, in constant time.
+#[inline]
+pub fn ct_lt_u8(x: u8, y: u8) -> Choice {
+    Choice(expose(_lt(secret(x), y)))
+}
+
+/// Checks whether `x > y`, in constant time.
+#[inline]
+pub fn ct_gt_u8(x: u8, y: u8) -> Choice {
+    Choice(expose(_gt(secret(x), y)))
+}
+
+/// Checks whether `x <= y`, in constant time.
+#[inline]
+pub fn ct_le_u8(x: u8, y: u8) -> Choice {
+    Choice(expose(_le(secret(x), y)))
+}
+
+/// Checks whether `x >= y`, in constant time.
+#[inline]
+pub fn ct_ge_u8(x: u8, y: u8) -> Choice {
+    Choice(expose(_ge(secret(x), y)))
+}
+
+/// Checks whether `lo <= x <= hi`, in constant time.
+#[inline]
+pub fn ct_in_range(x: u8, lo: u8, hi: u8) -> Choice {
+    Choice(expose(_in_range(secret(x), lo, hi)))
+}
+
+/// Maps the range `lo..=hi` to `base..`, in constant time.
+///
+/// Character classes of an alphabet can be mapped to their values, or values
+/// to characters, by combining ranges with `|`.
+///
+/// # Arguments
+///
+/// * `x` - Byte to map
+/// * `lo` - First byte of the range
+/// * `hi` - Last byte of the range
+/// * `base` - Value `lo` is mapped to
+///
+/// # Returns
+///
+/// `x - lo + base` (wrapping) if `x` is in the range, 0 otherwise.
+#[inline]
+pub fn ct_map_range(x: u8, lo: u8, hi: u8, base: u8) -> u8 {
+    expose(_map_range(secret(x), lo, hi, base))
+}
+
+/// Returns `a` if `choice` is true, `b` otherwise, in constant time.
+#[inline]
+pub fn ct_select(choice: Choice, a: u8, b: u8) -> u8 {
+    expose(_select(secret(choice.0), secret(a), secret(b)))
+}
+
+/// Copies `src` into `dst` if `choice` is true, in constant time.
+///
+/// Both slices are entirely read and `dst` is entirely written in either case.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn ct_copy_if(choice: Choice, dst: &mut [u8], src: &[u8]) {
+    assert_eq!(dst.len(), src.len(), "slices have different lengths");
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d = expose(_select(secret(choice.0), secret(s), secret(*d)));
+    }
+}
+
+/// Swaps the contents of `a` and `b` if `choice` is true, in constant time.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn ct_swap_if(choice: Choice, a: &mut [u8], b: &mut [u8]) {
+    assert_eq!(a.len(), b.len(), "slices have different lengths");
+    for (x, y) in a.iter_mut().zip(b.iter_mut()) {
+        let t = choice.0 & (*x ^ *y);
+        *x ^= t;
+        *y ^= t;
+    }
+}
+
+/// Result of a constant-time comparison of two byte strings.
+///
+/// The ordering is kept as masks, so that it can be combined with other
+/// conditions, e.g. to look up a key in a sorted table without revealing
+/// where it is.
+#[derive(Copy, Clone, Debug)]
+pub struct CtOrdering {
+    lt: u8,
+    gt: u8,
+}
+
+impl CtOrdering {
+    /// Returns whether the first string is less than the second one.
+    #[inline]
+    pub fn is_lt(self) -> Choice {
+        Choice(self.lt)
+    }
+
+    /// Returns whether the first string is greater than the second one.
+    #[inline]
+    pub fn is_gt(self) -> Choice {
+        Choice(self.gt)
+    }
+
+    /// Returns whether the strings are equal.
+    #[inline]
+    pub fn is_eq(self) -> Choice {
+        Choice(!(self.lt | self.gt))
+    }
+}
+
+impl From<CtOrdering> for core::cmp::Ordering {
+    #[inline]
+    fn from(ordering: CtOrdering) -> Self {
+        (ordering.gt & 1).cmp(&(ordering.lt & 1))
+    }
+}
+
+// Accumulates the differences between two slices of the same length.
+#[inline]
+fn diff(x: &[u8], y: &[u8]) -> Secret {
+    let mut d = Secret::new(0);
+    for (&a, &b) in x.iter().zip(y) {
+        d |= Secret::new(a) ^ Secret::new(b);
+    }
+    d
+}
+
+/// Compares two byte strings in lexicographic order, like `memcmp()`.
+///
+/// The content of the strings is compared in constant time. Lengths are
+/// not secret: a string that is a prefix of the other one is less than it.
+pub fn ct_compare(x: &[u8], y: &[u8]) -> CtOrdering {
+    let len = x.len().min(y.len());
+    let mut lt = Secret::new(Choice::from_bool(x.len() < y.len()).0);
+    let mut gt = Secret::new(Choice::from_bool(x.len() > y.len()).0);
+    // Going backwards, every differing byte overrides the result of the next ones.
+    for (&a, &b) in x[..len].iter().zip(&y[..len]).rev() {
+        let (a, b) = (Secret::new(a), Secret::new(b));
+        let eq = _eq(a ^ b, 0);
+        lt = _select(eq, lt, borrow(a, b));
+        gt = _select(eq, gt, borrow(b, a));
+    }
+    CtOrdering {
+        lt: lt.expose(),
+        gt: gt.expose(),
+    }
+}
+
+/// Checks whether two byte strings are equal, in constant time.
+///
+/// Lengths are not secret: strings of different lengths are not equal.
+pub fn ct_eq(x: &[u8], y: &[u8]) -> Choice {
+    if x.len() != y.len() {
+        return Choice::FALSE;
+    }
+    Choice(_eq(diff(x, y), 0).expose())
+}
+
+/// Checks whether all the bytes of a string are zero, in constant time.
+pub fn ct_is_zero(x: &[u8]) -> Choice {
+    let mut d = Secret::new(0);
+    for &a in x {
+        d |= Secret::new(a);
+    }
+    Choice(_eq(d, 0).expose())
+}
+
+/// Checks whether `x` starts with `prefix`, in constant time.
+///
+/// Lengths are not secret: if `prefix` is longer than `x`, this returns false.
+pub fn ct_starts_with(x: &[u8], prefix: &[u8]) -> Choice {
+    if prefix.len() > x.len() {
+        return Choice::FALSE;
+    }
+    ct_eq(&x[..prefix.len()], prefix)
+}
+
+/// A byte that panics if it is branched on, compared or used as an index.
+///
+/// With `u8` bytes, code on `Secret` values could compare them, convert them
+/// to `bool` or use them as an index. With `Tainted` bytes, the same code
+/// compiles but panics. Values returned by [`expose`] are plain bytes, and are
+/// not tracked.
+#[cfg(any(test, feature = "ct-taint"))]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Tainted(u8);
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl BitAnd for Tainted {
+    type Output = Self;
+
+    fn bitand(self, y: Self) -> Self {
+        Tainted(self.0 & y.0)
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl BitAndAssign for Tainted {
+    fn bitand_assign(&mut self, y: Self) {
+        self.0 &= y.0;
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl BitOr for Tainted {
+    type Output = Self;
+
+    fn bitor(self, y: Self) -> Self {
+        Tainted(self.0 | y.0)
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl BitOrAssign for Tainted {
+    fn bitor_assign(&mut self, y: Self) {
+        self.0 |= y.0;
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl BitXor for Tainted {
+    type Output = Self;
+
+    fn bitxor(self, y: Self) -> Self {
+        Tainted(self.0 ^ y.0)
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl Not for Tainted {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Tainted(!self.0)
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl PartialEq for Tainted {
+    fn eq(&self, _: &Self) -> bool {
+        panic!("secret byte compared")
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl PartialOrd for Tainted {
+    fn partial_cmp(&self, _: &Self) -> Option<core::cmp::Ordering> {
+        panic!("secret byte compared")
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl From<Tainted> for bool {
+    fn from(_: Tainted) -> bool {
+        panic!("secret byte converted to a boolean")
+    }
+}
+
+#[cfg(any(test, feature = "ct-taint"))]
+impl From<Tainted> for usize {
+    fn from(_: Tainted) -> usize {
+        panic!("secret byte used as an index")
+    }
+}
+
+#[test]
+fn test_primitives() {
+    for x in 0..=255u8 {
+        for y in 0..=255u8 {
+            let t = Tainted(x);
+            let mask = |b: bool| if b { 0xff } else { 0 };
+            assert_eq!(_eq(t, y).expose(), mask(x == y));
+            assert_eq!(_gt(t, y).expose(), mask(x > y));
+            assert_eq!(_lt(t, y).expose(), mask(x < y));
+            assert_eq!(_ge(t, y).expose(), mask(x >= y));
+            assert_eq!(_le(t, y).expose(), mask(x <= y));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "secret byte compared")]
+fn test_tainted_compare() {
+    let _ = Tainted(1) == Tainted(1);
+}
+
+#[test]
+#[should_panic(expected = "secret byte converted to a boolean")]
+fn test_tainted_bool() {
+    let _ = bool::from(Tainted(1));
+}
+
+#[test]
+#[should_panic(expected = "secret byte used as an index")]
+fn test_tainted_index() {
+    let _ = [0u8; 1][usize::from(Tainted(0))];
+}
+
+#[test]
+fn test_ct() {
+    for x in 0..=255u8 {
+        for y in 0..=255u8 {
+            assert_eq!(bool::from(ct_eq_u8(x, y)), x == y);
+            assert_eq!(bool::from(ct_lt_u8(x, y)), x < y);
+            assert_eq!(bool::from(ct_gt_u8(x, y)), x > y);
+            assert_eq!(bool::from(ct_le_u8(x, y)), x <= y);
+            assert_eq!(bool::from(ct_ge_u8(x, y)), x >= y);
+            let choice = ct_lt_u8(x, y);
+            assert_eq!(ct_select(choice, x, y), x.min(y));
+            assert_eq!(ct_select(!choice, x, y), x.max(y));
+        }
+        assert_eq!(
+            bool::from(ct_in_range(x, b'a', b'f')),
+            (b'a'..=b'f').contains(&x)
+        );
+        let mapped = ct_map_range(x, b'a', b'f', 10);
+        let expected = if (b'a'..=b'f').contains(&x) {
+            x - b'a' + 10
+        } else {
+            0
+        };
+        assert_eq!(mapped, expected);
+    }
+    assert_eq!(Choice::from_bool(true).mask(), Choice::TRUE.mask());
+    assert_eq!(Choice::from_bool(false).mask(), Choice::FALSE.mask());
+    assert_eq!((Choice::TRUE & Choice::FALSE).mask(), 0);
+    assert_eq!((Choice::TRUE | Choice::FALSE).mask(), 0xff);
+    assert_eq!((Choice::TRUE ^ Choice::TRUE).mask(), 0);
+}
+
+#[test]
+fn test_ct_copy_swap() {
+    let mut dst = [1u8, 2, 3];
+    ct_copy_if(Choice::FALSE, &mut dst, &[4, 5, 6]);
+    assert_eq!(dst, [1, 2, 3]);
+    ct_copy_if(Choice::TRUE, &mut dst, &[4, 5, 6]);
+    assert_eq!(dst, [4, 5, 6]);
+
+    let (mut a, mut b) = ([1u8, 2], [3u8, 4]);
+    ct_swap_if(Choice::FALSE, &mut a, &mut b);
+    assert_eq!((a, b), ([1, 2], [3, 4]));
+    ct_swap_if(Choice::TRUE, &mut a, &mut b);
+    assert_eq!((a, b), ([3, 4], [1, 2]));
+}
+
+#[test]
+#[should_panic]
+fn test_ct_copy_length_mismatch() {
+    ct_copy_if(Choice::TRUE, &mut [0u8; 2], &[0u8; 3]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_ct_compare() {
+    // Strings of up to 3 bytes, with values around the edges of signed and
+    // unsigned comparisons.
+    let alphabet = [0u8, 1, 0x7f, 0x80, 0xfe, 0xff];
+    let mut strings = vec![vec![]];
+    for len in 1..=3u32 {
+        for i in 0..alphabet.len().pow(len) {
+            let s: Vec<u8> = (0..len)
+                .map(|j| alphabet[i / alphabet.len().pow(j) % alphabet.len()])
+                .collect();
+            strings.push(s);
+        }
+    }
+    for x in &strings {
+        for y in &strings {
+            let ordering = ct_compare(x, y);
+            assert_eq!(core::cmp::Ordering::from(ordering), x.cmp(y));
+            assert_eq!(bool::from(ordering.is_lt()), x < y);
+            assert_eq!(bool::from(ordering.is_gt()), x > y);
+            assert_eq!(bool::from(ordering.is_eq()), x == y);
+            assert_eq!(bool::from(ct_eq(x, y)), x == y);
+            assert_eq!(bool::from(ct_starts_with(x, y)), x.starts_with(y));
+        }
+        assert_eq!(bool::from(ct_is_zero(x)), x.iter().all(|&a| a == 0));
+    }
+    for a in 0..=255u8 {
+        for b in 0..=255u8 {
+            let ordering = ct_compare(&[a], &[b]);
+            assert_eq!(core::cmp::Ordering::from(ordering), a.cmp(&b));
+            assert_eq!(bool::from(ct_eq(&[a, b], &[b, a])), a == b);
+        }
+        assert_eq!(bool::from(ct_is_zero(&[0, a, 0])), a == 0);
+    }
+}
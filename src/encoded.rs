@@ -0,0 +1,374 @@
+use core::fmt::{self, Debug, Display};
+use core::marker::PhantomData;
+use core::str::FromStr;
+
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::error::*;
+use crate::transcode::encode_chunks;
+#[cfg(feature = "alloc")]
+use crate::{clear, verify_encoded, DecoderExt, Encoder};
+use crate::{verify, Decoder, EncoderExt};
+
+/// A string that is known to be valid for the codec `C`.
+///
+/// Values can only be created by encoding binary data, or by parsing a string
+/// that passes the same strict checks as [`Decoder::decode`]. Comparisons are
+/// done in constant time on the decoded data, so that strings that only differ
+/// in a way the codec accepts (such as the case of hexadecimal digits) are
+/// equal. The content is not shown by `Debug`.
+///
+/// This type is only available when the `alloc` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::{Base64UrlSafeNoPadding, Encoded};
+///
+/// fn example() -> Result<(), ct_codecs::Error> {
+///     let token: Encoded<Base64UrlSafeNoPadding> = "----3g".parse()?;
+///     assert_eq!(token, Encoded::encode([251, 239, 190, 222])?);
+///     assert_eq!(token.to_string(), "----3g");
+///     assert_eq!(format!("{:?}", token), "Encoded(<redacted>)");
+///
+///     assert!("----3g==".parse::<Encoded<Base64UrlSafeNoPadding>>().is_err());
+///     Ok(())
+/// }
+/// # example().unwrap();
+/// ```
+#[cfg(feature = "alloc")]
+pub struct Encoded<C> {
+    encoded: String,
+    codec: PhantomData<C>,
+}
+
+#[cfg(feature = "alloc")]
+impl<C: Encoder> Encoded<C> {
+    /// Encodes binary data with `C`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Binary input data to encode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Encoded<C>)` - The encoded data
+    /// * `Err(Error::Overflow)` - If the calculation would overflow
+    pub fn encode<IN: AsRef<[u8]>>(bin: IN) -> Result<Self, Error> {
+        Ok(Encoded {
+            encoded: C::encode_to_string(bin)?,
+            codec: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: Decoder> Encoded<C> {
+    /// Decodes the data into a `Vec<u8>`.
+    pub fn decode_to_vec(&self) -> Result<Vec<u8>, Error> {
+        C::decode_to_vec(&self.encoded, None)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C> Encoded<C> {
+    /// Returns the encoded string.
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+
+    /// Returns the encoded string, consuming the wrapper.
+    pub fn into_string(mut self) -> String {
+        core::mem::take(&mut self.encoded)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: DecoderExt> FromStr for Encoded<C> {
+    type Err = Error;
+
+    fn from_str(encoded: &str) -> Result<Self, Error> {
+        C::validate(encoded, None)?;
+        Ok(Encoded {
+            encoded: encoded.to_string(),
+            codec: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C> AsRef<str> for Encoded<C> {
+    fn as_ref(&self) -> &str {
+        &self.encoded
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C> Clone for Encoded<C> {
+    fn clone(&self) -> Self {
+        Encoded {
+            encoded: self.encoded.clone(),
+            codec: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: DecoderExt> PartialEq for Encoded<C> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut bin = match self.decode_to_vec() {
+            Ok(bin) => bin,
+            Err(_) => return false,
+        };
+        let res = verify_encoded::<C, _>(&other.encoded, &bin);
+        clear(&mut bin);
+        res
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: DecoderExt> Eq for Encoded<C> {}
+
+#[cfg(feature = "alloc")]
+impl<C> Display for Encoded<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encoded)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C> Debug for Encoded<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Encoded(<redacted>)")
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "zeroize"))]
+impl<C> Drop for Encoded<C> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.encoded);
+    }
+}
+
+/// Exactly `N` bytes of binary data, parsed from and displayed using the codec `C`.
+///
+/// Parsing decodes the input with [`Decoder::decode`], and fails if the decoded
+/// data is not exactly `N` bytes long. Comparisons are done in constant time,
+/// and the content is not shown by `Debug`.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::{Decoded, Hex};
+///
+/// fn example() -> Result<(), ct_codecs::Error> {
+///     let key: Decoded<Hex, 4> = "deadbeef".parse()?;
+///     assert_eq!(key.as_bytes(), &[0xde, 0xad, 0xbe, 0xef]);
+///     assert_eq!(key.to_string(), "deadbeef");
+///     assert_eq!(format!("{:?}", key), "Decoded(<redacted>)");
+///
+///     assert!("deadbe".parse::<Decoded<Hex, 4>>().is_err());
+///     Ok(())
+/// }
+/// # example().unwrap();
+/// ```
+pub struct Decoded<C, const N: usize> {
+    bin: [u8; N],
+    codec: PhantomData<C>,
+}
+
+impl<C, const N: usize> Decoded<C, N> {
+    /// Wraps binary data.
+    pub fn new(bin: [u8; N]) -> Self {
+        Decoded {
+            bin,
+            codec: PhantomData,
+        }
+    }
+
+    /// Returns the binary data.
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.bin
+    }
+
+    /// Returns a copy of the binary data.
+    pub fn to_bytes(&self) -> [u8; N] {
+        self.bin
+    }
+}
+
+impl<C, const N: usize> From<[u8; N]> for Decoded<C, N> {
+    fn from(bin: [u8; N]) -> Self {
+        Decoded::new(bin)
+    }
+}
+
+impl<C: Decoder, const N: usize> FromStr for Decoded<C, N> {
+    type Err = Error;
+
+    fn from_str(encoded: &str) -> Result<Self, Error> {
+        let mut decoded = Decoded::new([0u8; N]);
+        if C::decode(&mut decoded.bin, encoded, None)?.len() != N {
+            return Err(Error::InvalidInput);
+        }
+        Ok(decoded)
+    }
+}
+
+impl<C, const N: usize> AsRef<[u8]> for Decoded<C, N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bin
+    }
+}
+
+impl<C, const N: usize> Clone for Decoded<C, N> {
+    fn clone(&self) -> Self {
+        Decoded::new(self.bin)
+    }
+}
+
+impl<C, const N: usize> PartialEq for Decoded<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        verify(&self.bin, &other.bin)
+    }
+}
+
+impl<C, const N: usize> Eq for Decoded<C, N> {}
+
+impl<C: EncoderExt, const N: usize> Display for Decoded<C, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        encode_chunks::<C, _>(&self.bin, |encoded| f.write_str(encoded))
+    }
+}
+
+impl<C, const N: usize> Debug for Decoded<C, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Decoded(<redacted>)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<C, const N: usize> Drop for Decoded<C, N> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.bin);
+    }
+}
+
+/// Binary data that is encoded with the codec `C` when formatted.
+///
+/// Returned by [`EncoderExt::display`]. For [`Hex`](crate::Hex), `LowerHex` and
+/// `UpperHex` are also implemented, and the alternate flag (`{:#x}`) adds a
+/// `0x` prefix.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::{Base64, EncoderExt, Hex};
+///
+/// let fingerprint = [0xde, 0xad, 0xbe, 0xef];
+/// assert_eq!(format!("{}", Base64::display(&fingerprint)), "3q2+7w==");
+/// assert_eq!(format!("{:X}", Hex::display(&fingerprint)), "DEADBEEF");
+/// assert_eq!(format!("{:#x}", Hex::display(&fingerprint)), "0xdeadbeef");
+/// ```
+pub struct EncodedDisplay<'a, C> {
+    bin: &'a [u8],
+    codec: PhantomData<C>,
+}
+
+impl<'a, C> EncodedDisplay<'a, C> {
+    pub(crate) fn new(bin: &'a [u8]) -> Self {
+        EncodedDisplay {
+            bin,
+            codec: PhantomData,
+        }
+    }
+
+    /// Returns the binary data.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bin
+    }
+}
+
+impl<C> Clone for EncodedDisplay<'_, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for EncodedDisplay<'_, C> {}
+
+impl<C: EncoderExt> Display for EncodedDisplay<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        encode_chunks::<C, _>(self.bin, |encoded| f.write_str(encoded))
+    }
+}
+
+impl<C> Debug for EncodedDisplay<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncodedDisplay(<redacted>)")
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_encoded() {
+    use crate::*;
+
+    let a: Encoded<Base64> = "AQULDxODeg==".parse().unwrap();
+    let b = Encoded::<Base64>::encode([1u8, 5, 11, 15, 19, 131, 122]).unwrap();
+    assert_eq!(a, b);
+    assert_ne!(a, "AQULDxODew==".parse().unwrap());
+    assert_ne!(a, "AQULDxOD".parse().unwrap());
+    assert_eq!(a.as_str(), "AQULDxODeg==");
+    assert_eq!(a.decode_to_vec().unwrap(), [1, 5, 11, 15, 19, 131, 122]);
+    assert_eq!(format!("{}", a), "AQULDxODeg==");
+    assert_eq!(format!("{:?}", a), "Encoded(<redacted>)");
+    assert_eq!(a.clone().into_string(), "AQULDxODeg==");
+
+    assert!("AQULDxODeg".parse::<Encoded<Base64>>().is_err());
+    assert!("AQULDxODeh==".parse::<Encoded<Base64>>().is_err());
+    assert!("MZXW6YTBOI".parse::<Encoded<Base32NoPadding>>().is_ok());
+    assert!("mzxw6ytboi".parse::<Encoded<Base32NoPadding>>().is_err());
+
+    // Mixed-case hex strings encoding the same data are equal.
+    let lower: Encoded<Hex> = "deadbeef".parse().unwrap();
+    let upper: Encoded<Hex> = "DEADBEEF".parse().unwrap();
+    let mixed: Encoded<Hex> = "DeAdBeEf".parse().unwrap();
+    assert_eq!(lower, upper);
+    assert_eq!(lower, mixed);
+    assert_eq!(upper, Encoded::encode([0xde, 0xad, 0xbe, 0xef]).unwrap());
+    assert_ne!(upper, "DEADBEEE".parse().unwrap());
+    assert_ne!(upper, "DEADBE".parse().unwrap());
+    assert_eq!(mixed.as_str(), "DeAdBeEf");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_decoded() {
+    use crate::*;
+
+    let bin: Vec<u8> = (0..200u32).map(|i| (i * 31) as u8).collect();
+    let mut array = [0u8; 200];
+    array.copy_from_slice(&bin);
+    let hex = Hex::encode_to_string(&bin).unwrap();
+    let b32 = Base32::encode_to_string(&bin).unwrap();
+
+    let a: Decoded<Hex, 200> = hex.parse().unwrap();
+    assert_eq!(a.as_bytes(), &array);
+    assert_eq!(a.to_string(), hex);
+    let b = Decoded::<Base32, 200>::from(array);
+    assert_eq!(b.to_string(), b32);
+    assert_eq!(b32.parse::<Decoded<Base32, 200>>().unwrap(), b);
+    assert_eq!(format!("{:?}", b), "Decoded(<redacted>)");
+
+    array[199] ^= 1;
+    assert_ne!(a, Decoded::new(array));
+    assert_eq!(
+        hex[2..].parse::<Decoded<Hex, 200>>(),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(hex.parse::<Decoded<Hex, 199>>(), Err(Error::Overflow));
+}
@@ -34,3 +34,95 @@ impl Display for Error {
         }
     }
 }
+
+/// Detailed reason why an input couldn't be decoded.
+///
+/// This is only returned by the [`DecoderExt::diagnose`](crate::DecoderExt::diagnose)
+/// path, which is not constant-time. The regular decoding functions return
+/// the coarser [`Error`] type.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ErrorKind {
+    /// A character is neither part of the alphabet nor ignored.
+    InvalidCharacter,
+
+    /// Padding is missing, incomplete, unexpected, or followed by more data.
+    InvalidPadding,
+
+    /// The unused bits of the last character are not zero.
+    NonCanonical,
+
+    /// The number of encoded characters can't correspond to any binary length.
+    InvalidLength,
+
+    /// The output buffer is too small; `needed` is the decoded length.
+    BufferTooSmall {
+        /// Required length of the output buffer
+        needed: usize,
+    },
+}
+
+/// Detailed decoding error, with the offset of the offending character.
+///
+/// `offset` is the position in the encoded input where the error was detected.
+/// It is equal to the input length if more characters were expected, and
+/// `None` if the error is not related to a specific position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// The kind of error
+    pub kind: ErrorKind,
+    /// The offset of the offending character in the encoded input, if any
+    pub offset: Option<usize>,
+}
+
+impl Diagnostic {
+    #[inline]
+    pub(crate) fn at(kind: ErrorKind, offset: usize) -> Self {
+        Diagnostic {
+            kind,
+            offset: Some(offset),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn buffer_too_small(needed: usize) -> Self {
+        Diagnostic {
+            kind: ErrorKind::BufferTooSmall { needed },
+            offset: None,
+        }
+    }
+}
+
+impl From<Diagnostic> for Error {
+    fn from(diagnostic: Diagnostic) -> Self {
+        match diagnostic.kind {
+            ErrorKind::BufferTooSmall { .. } => Error::Overflow,
+            _ => Error::InvalidInput,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Diagnostic {}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::InvalidCharacter => write!(f, "Invalid character"),
+            ErrorKind::InvalidPadding => write!(f, "Invalid padding"),
+            ErrorKind::NonCanonical => write!(f, "Non-canonical encoding"),
+            ErrorKind::InvalidLength => write!(f, "Invalid length"),
+            ErrorKind::BufferTooSmall { needed } => {
+                write!(f, "Output buffer too small ({} bytes needed)", needed)
+            }
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "{} at offset {}", self.kind, offset),
+            None => self.kind.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Fuzzing entry points, and a deterministic mutation driver.
+//!
+//! Every target takes an arbitrary byte string and panics if a property of
+//! the codecs doesn't hold:
+//!
+//! * [`roundtrip`]: decoding encoded data returns the original data
+//! * [`decode`]: decoding arbitrary input never panics, the decoded length is
+//!   at most [`Decoder::max_decoded_len`], and every decoding function agrees
+//! * [`canonical`]: when arbitrary input decodes, re-encoding the result
+//!   returns the input
+//!
+//! [`base64()`], [`base32()`] and [`hex()`] run all of them on every variant of a
+//! codec. They can be called from a `cargo fuzz` target, as done in the
+//! `fuzz/` directory, or from [`drive`], which mutates a corpus
+//! deterministically and runs on stable Rust, under `cargo test`.
+//!
+//! This module is only available when the `fuzzing` feature is enabled. It is
+//! meant for testing, and is not covered by the semver guarantees of the
+//! crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use ct_codecs::fuzzing;
+//!
+//! let seeds: &[&[u8]] = &[b"\x00Zm9vYg==", b"\x01Zm9v YmFy"];
+//! fuzzing::drive(fuzzing::base64, seeds, 1000, 42);
+//! ```
+
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::{vec, vec::Vec};
+
+use crate::*;
+
+// The random number generator of the tests.
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::Rng;
+
+// The first byte of the input to [`decode`] selects the characters to
+// ignore. These include padding and characters that are part of some
+// alphabets, to get as many paths through the padding checks as possible.
+const IGNORE_SETS: [Option<&[u8]>; 4] = [None, Some(b" \n"), Some(b"="), Some(b" =-_\n")];
+
+/// Checks that decoding encoded data returns the original data.
+///
+/// # Arguments
+///
+/// * `data` - Binary data to encode
+///
+/// # Panics
+///
+/// If any encoding or decoding function fails or disagrees with the others.
+pub fn roundtrip<C: EncoderExt + DecoderExt>(data: &[u8]) {
+    let encoded_len = C::encoded_len(data.len()).unwrap();
+    let mut encoded = vec![0u8; encoded_len];
+    let encoded = C::encode(&mut encoded, data).unwrap();
+    assert_eq!(encoded.len(), encoded_len);
+    assert!(C::max_decoded_len(encoded_len) >= data.len());
+
+    let mut buf = vec![0u8; encoded_len];
+    buf[..data.len()].copy_from_slice(data);
+    assert_eq!(C::encode_in_place(&mut buf, data.len()).unwrap(), encoded);
+
+    let mut bin = vec![0u8; data.len()];
+    assert_eq!(C::decode(&mut bin, encoded, None).unwrap(), data);
+    assert_eq!(C::validate(encoded, None).unwrap(), data.len());
+    assert_eq!(C::decode_in_place(&mut buf, None).unwrap(), data);
+    if !data.is_empty() {
+        let mut bin = vec![0u8; data.len() - 1];
+        assert_eq!(C::decode(&mut bin, encoded, None), Err(Error::Overflow));
+    }
+}
+
+/// Checks that decoding arbitrary input never panics, and that every
+/// decoding function returns the same result.
+///
+/// # Arguments
+///
+/// * `data` - A byte selecting the characters to ignore, followed by the input to decode
+///
+/// # Panics
+///
+/// If the decoding functions disagree, or if the decoded data is longer than
+/// [`Decoder::max_decoded_len`].
+pub fn decode<D: DecoderExt>(data: &[u8]) {
+    let (ignore, encoded) = match data.split_first() {
+        Some((&selector, encoded)) => (IGNORE_SETS[selector as usize % IGNORE_SETS.len()], encoded),
+        None => (None, data),
+    };
+    let max_decoded_len = D::max_decoded_len(encoded.len());
+    assert!(max_decoded_len <= encoded.len());
+
+    let mut bin = vec![0u8; max_decoded_len];
+    let res = D::decode(&mut bin, encoded, ignore).map(|bin| bin.to_vec());
+    assert_eq!(
+        D::validate(encoded, ignore),
+        res.as_ref().map(Vec::len).map_err(|&e| e)
+    );
+    let mut buf = encoded.to_vec();
+    assert_eq!(
+        D::decode_in_place(&mut buf, ignore).map(|bin| bin.to_vec()),
+        res
+    );
+    let mut bin = vec![0u8; max_decoded_len];
+    match D::diagnose(&mut bin, encoded, ignore) {
+        Ok(bin) => assert_eq!(Ok(bin.to_vec()), res),
+        Err(diagnostic) => {
+            assert_eq!(Err(Error::from(diagnostic)), res);
+            if let Some(offset) = diagnostic.offset {
+                assert!(offset <= encoded.len());
+            }
+        }
+    }
+    if let Ok(bin) = res {
+        if !bin.is_empty() {
+            let mut short = vec![0u8; bin.len() - 1];
+            assert_eq!(D::decode(&mut short, encoded, ignore), Err(Error::Overflow));
+        }
+    }
+}
+
+/// Checks that re-encoding decoded data returns the input.
+///
+/// Decoders only accept canonical encodings, so any input they accept
+/// without ignoring characters must be what the encoder produces.
+///
+/// # Arguments
+///
+/// * `data` - Input to decode
+///
+/// # Panics
+///
+/// If the input is accepted by the decoder, but not returned by the encoder.
+pub fn canonical<C: Encoder + Decoder>(data: &[u8]) {
+    let mut bin = vec![0u8; C::max_decoded_len(data.len())];
+    if let Ok(bin) = C::decode(&mut bin, data, None) {
+        let mut encoded = vec![0u8; C::encoded_len(bin.len()).unwrap()];
+        assert_eq!(C::encode(&mut encoded, bin).unwrap(), data);
+    }
+}
+
+/// Runs all the targets on every Base64 variant.
+///
+/// # Arguments
+///
+/// * `data` - Fuzzer input
+pub fn base64(data: &[u8]) {
+    roundtrip::<Base64>(data);
+    roundtrip::<Base64NoPadding>(data);
+    roundtrip::<Base64UrlSafe>(data);
+    roundtrip::<Base64UrlSafeNoPadding>(data);
+    decode::<Base64>(data);
+    decode::<Base64NoPadding>(data);
+    decode::<Base64UrlSafe>(data);
+    decode::<Base64UrlSafeNoPadding>(data);
+    decode::<Base64Mixed>(data);
+    decode::<Base64MixedNoPadding>(data);
+    canonical::<Base64>(data);
+    canonical::<Base64NoPadding>(data);
+    canonical::<Base64UrlSafe>(data);
+    canonical::<Base64UrlSafeNoPadding>(data);
+}
+
+/// Runs all the targets on every Base32 variant.
+///
+/// # Arguments
+///
+/// * `data` - Fuzzer input
+pub fn base32(data: &[u8]) {
+    roundtrip::<Base32>(data);
+    roundtrip::<Base32NoPadding>(data);
+    roundtrip::<Base32Hex>(data);
+    roundtrip::<Base32HexNoPadding>(data);
+    decode::<Base32>(data);
+    decode::<Base32NoPadding>(data);
+    decode::<Base32Hex>(data);
+    decode::<Base32HexNoPadding>(data);
+    canonical::<Base32>(data);
+    canonical::<Base32NoPadding>(data);
+    canonical::<Base32Hex>(data);
+    canonical::<Base32HexNoPadding>(data);
+}
+
+/// Runs all the targets on the hexadecimal codec.
+///
+/// Uppercase digits are accepted by the decoder, so the canonical check
+/// is done on the lowercased input.
+///
+/// # Arguments
+///
+/// * `data` - Fuzzer input
+pub fn hex(data: &[u8]) {
+    roundtrip::<Hex>(data);
+    decode::<Hex>(data);
+    canonical::<Hex>(&data.to_ascii_lowercase());
+}
+
+// Characters inserted by the mutator, to build inputs that are close to
+// being valid.
+const DICTIONARY: &[u8] = b"=====  \n\nAaQgz09+/-_MYVv";
+
+// Mutated inputs are truncated to that length. Longer inputs don't reach
+// more code, and would slow the driver down.
+const MAX_INPUT_LEN: usize = 256;
+
+fn mutate(rng: &mut Rng, input: &mut Vec<u8>, corpus: &[Vec<u8>]) {
+    let pos = rng.below(input.len() + 1);
+    match rng.below(8) {
+        0 if pos < input.len() => input[pos] ^= 1 << rng.below(8),
+        1 if pos < input.len() => input[pos] = rng.next() as u8,
+        2 if pos < input.len() => input[pos] = DICTIONARY[rng.below(DICTIONARY.len())],
+        3 if pos < input.len() => {
+            input.remove(pos);
+        }
+        4 => input.insert(pos, DICTIONARY[rng.below(DICTIONARY.len())]),
+        5 => input.truncate(pos),
+        6 if pos < input.len() => {
+            let end = pos + rng.below(input.len() - pos) + 1;
+            let chunk = input[pos..end].to_vec();
+            let at = rng.below(input.len() + 1);
+            input.splice(at..at, chunk);
+        }
+        _ => {
+            let other = &corpus[rng.below(corpus.len())];
+            let start = rng.below(other.len() + 1);
+            input.extend_from_slice(&other[start..]);
+        }
+    }
+}
+
+// Runs the target, and adds the input to the panic message if it fails.
+fn run_target<F: Fn(&[u8])>(target: &F, input: &[u8]) {
+    #[cfg(feature = "std")]
+    {
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| target(input)));
+        if let Err(e) = res {
+            let message = match e.downcast::<String>() {
+                Ok(message) => *message,
+                Err(e) => e.downcast_ref::<&str>().copied().unwrap_or("").into(),
+            };
+            panic!("{} (failing input: b\"{}\")", message, input.escape_ascii());
+        }
+    }
+    #[cfg(not(feature = "std"))]
+    target(input);
+}
+
+/// Runs a target on mutations of a corpus.
+///
+/// Every iteration picks an input from the corpus, applies a few random
+/// mutations to it (bit flips, byte changes, insertions of padding,
+/// whitespace and alphabet characters, deletions, truncations, duplications
+/// and splicing with other inputs), and runs the target on the result. The
+/// mutations only depend on `seed`, so a failure can be reproduced by running
+/// the driver again with the same parameters. With the `std` feature, the
+/// failing input is also added to the panic message.
+///
+/// # Arguments
+///
+/// * `target` - Function to call on every input, panicking on failure
+/// * `seeds` - Initial inputs, to which the mutated inputs are added
+/// * `iterations` - Number of mutated inputs to try
+/// * `seed` - Seed of the pseudo-random generator
+///
+/// # Panics
+///
+/// If `seeds` is empty, or if `target` panics.
+pub fn drive<F: Fn(&[u8])>(target: F, seeds: &[&[u8]], iterations: usize, seed: u64) {
+    assert!(!seeds.is_empty(), "the corpus can't be empty");
+    let mut corpus: Vec<Vec<u8>> = seeds.iter().map(|seed| seed.to_vec()).collect();
+    for input in &corpus {
+        run_target(&target, input);
+    }
+    let mut rng = Rng(seed | 1);
+    for _ in 0..iterations {
+        let mut input = corpus[rng.below(corpus.len())].clone();
+        for _ in 0..=rng.below(4) {
+            mutate(&mut rng, &mut input, &corpus);
+        }
+        input.truncate(MAX_INPUT_LEN);
+        run_target(&target, &input);
+        // Keep a bounded corpus, so that mutations can build on each other.
+        if corpus.len() < 1024 {
+            corpus.push(input);
+        } else {
+            let i = rng.below(corpus.len());
+            corpus[i] = input;
+        }
+    }
+}
+
+#[test]
+fn test_drive() {
+    let seeds: &[&[u8]] = &[b"", b"\x00Zm9vYg==", b"\x02MZXW6===", b"\x0166:6f"];
+    drive(base64, seeds, 200, 1);
+    drive(base32, seeds, 200, 2);
+    drive(hex, seeds, 200, 3);
+}
+
+#[test]
+#[should_panic]
+fn test_drive_failure() {
+    drive(
+        |data: &[u8]| assert!(!data.contains(&b'=')),
+        &[b"Zm9v"],
+        1000,
+        1,
+    );
+}
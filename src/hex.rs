@@ -1,5 +1,11 @@
+use core::fmt;
+
+use crate::buffer::*;
+use crate::ct::*;
 use crate::error::*;
-use crate::{Decoder, Encoder};
+use crate::literal::impl_decode_array;
+use crate::transcode::CHUNK_DECODED_MAXLEN;
+use crate::{clear, Decoder, DecoderExt, EncodedDisplay, Encoder};
 
 /// Hexadecimal encoder and decoder implementation.
 ///
@@ -42,6 +48,8 @@ use crate::{Decoder, Encoder};
 /// ```
 pub struct Hex;
 
+impl_decode_array!(Hex, "hex!", decode_array);
+
 impl Encoder for Hex {
     /// Calculates the encoded length for a hexadecimal representation.
     ///
@@ -81,22 +89,119 @@ impl Encoder for Hex {
         if hex_maxlen < bin_len.checked_shl(1).ok_or(Error::Overflow)? {
             return Err(Error::Overflow);
         }
-        for (i, v) in bin.iter().enumerate() {
-            let (b, c) = ((v >> 4) as u16, (v & 0xf) as u16);
-            let x = (((87 + c + (((c.wrapping_sub(10)) >> 8) & !38)) as u8) as u16) << 8
-                | ((87 + b + (((b.wrapping_sub(10)) >> 8) & !38)) as u8) as u16;
-            hex[i * 2] = x as u8;
-            hex[i * 2 + 1] = (x >> 8) as u8;
+        for (i, &v) in bin.iter().enumerate() {
+            hex[i * 2] = nibble_to_char(Secret::new(v >> 4)).expose();
+            hex[i * 2 + 1] = nibble_to_char(Secret::new(v & 0xf)).expose();
         }
         Ok(&hex[..bin_len * 2])
     }
 }
 
+// Maps a 4-bit value to a lowercase hexadecimal character.
+#[inline]
+const fn nibble_to_char(x: Secret) -> Secret {
+    or(_map_range(x, 0, 9, b'0'), _map_range(x, 10, 15, b'a'))
+}
+
+// Returns the value of a hexadecimal character of any case, and 0xff if it was valid.
+#[inline]
+const fn char_to_nibble(c: Secret) -> (Secret, Secret) {
+    let upper = and(c, secret(!0x20));
+    let x = or(
+        _map_range(c, b'0', b'9', 0),
+        _map_range(upper, b'A', b'F', 10),
+    );
+    (
+        x,
+        or(_in_range(c, b'0', b'9'), _in_range(upper, b'A', b'F')),
+    )
+}
+
+// Strict decoder usable in constant expressions. Characters cannot be
+// ignored, and the decoded data must be exactly `N` bytes long.
+const fn decode_array<const N: usize>(hex: &[u8]) -> Result<[u8; N], Error> {
+    let mut bin = [0u8; N];
+    let mut bin_pos = 0;
+    let mut state = false;
+    let mut c_acc = 0;
+    let mut hex_pos = 0;
+    while hex_pos < hex.len() {
+        let (c_val, valid) = char_to_nibble(secret(hex[hex_pos]));
+        let (c_val, valid) = (expose(c_val), expose(valid));
+        if valid == 0 {
+            return Err(Error::InvalidInput);
+        }
+        if !state {
+            c_acc = c_val << 4;
+        } else {
+            if bin_pos >= N {
+                return Err(Error::Overflow);
+            }
+            bin[bin_pos] = c_acc | c_val;
+            bin_pos += 1;
+        }
+        state = !state;
+        hex_pos += 1;
+    }
+    if state || bin_pos != N {
+        return Err(Error::InvalidInput);
+    }
+    Ok(bin)
+}
+
+// Writes hex-encoded data to a formatter, optionally with uppercase letters.
+fn fmt_hex(bin: &[u8], uppercase: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if f.alternate() {
+        f.write_str("0x")?;
+    }
+    let case_mask = Secret::new(0x20 & Choice::from_bool(uppercase).mask());
+    let mut hex = [0u8; 2 * CHUNK_DECODED_MAXLEN];
+    let res = bin.chunks(CHUNK_DECODED_MAXLEN).try_for_each(|chunk| {
+        let hex_len = Hex::encode(&mut hex, chunk).map_err(|_| fmt::Error)?.len();
+        for c in hex[..hex_len].iter_mut() {
+            // Letters are above '9'; clear their lowercase bit if requested.
+            *c ^= (_gt(Secret::new(*c), b'9') & case_mask).expose();
+        }
+        f.write_str(core::str::from_utf8(&hex[..hex_len]).unwrap())
+    });
+    clear(&mut hex);
+    res
+}
+
+impl fmt::LowerHex for EncodedDisplay<'_, Hex> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_hex(self.as_bytes(), false, f)
+    }
+}
+
+impl fmt::UpperHex for EncodedDisplay<'_, Hex> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_hex(self.as_bytes(), true, f)
+    }
+}
+
 impl Decoder for Hex {
+    /// Returns the maximum length of the data decoded from hexadecimal data.
+    ///
+    /// # Arguments
+    ///
+    /// * `hex_len` - The length of the hexadecimal data
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - Half the length of the hexadecimal data
+    #[inline]
+    fn max_decoded_len(hex_len: usize) -> usize {
+        hex_len / 2
+    }
+
     /// Decodes hexadecimal data back into its binary representation.
     ///
     /// The decoding is performed in constant time relative to the input length.
     /// Both uppercase and lowercase hexadecimal characters are accepted.
+    /// `Error::Overflow` is only returned when a complete byte doesn't fit in
+    /// the output buffer, so a trailing unpaired digit is reported as
+    /// `Error::InvalidInput`, even if the buffer is already full.
     ///
     /// # Arguments
     ///
@@ -114,41 +219,128 @@ impl Decoder for Hex {
         hex: IN,
         ignore: Option<&[u8]>,
     ) -> Result<&'t [u8], Error> {
+        let buffers = DecodeBuffers::Split {
+            input: hex.as_ref(),
+            output: Some(&mut *bin),
+        };
+        let bin_len = decode_raw(buffers, ignore)?;
+        Ok(&bin[..bin_len])
+    }
+}
+
+impl DecoderExt for Hex {
+    /// Validates hexadecimal data without decoding it.
+    ///
+    /// This runs the same checks as [`Hex::decode`](Decoder::decode), in
+    /// constant time relative to the input length.
+    ///
+    /// # Arguments
+    ///
+    /// * `hex` - Hexadecimal input data to validate
+    /// * `ignore` - Optional set of characters to ignore
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The length of the decoded data
+    /// * `Err(Error::InvalidInput)` - If the input contains invalid characters or has odd length
+    #[inline]
+    fn validate<IN: AsRef<[u8]>>(hex: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+        let buffers = DecodeBuffers::Split {
+            input: hex.as_ref(),
+            output: None,
+        };
+        decode_raw(buffers, ignore)
+    }
+
+    /// Decodes hexadecimal data over itself.
+    ///
+    /// The decoding is performed in constant time relative to the input length.
+    /// If an error is returned, the content of the buffer is unspecified.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer containing the hexadecimal data, overwritten with the decoded data
+    /// * `ignore` - Optional set of characters to ignore during decoding
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - A slice of the buffer containing the decoded data
+    /// * `Err(Error::InvalidInput)` - If the input contains invalid characters or has odd length
+    #[inline]
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error> {
+        let bin_len = decode_raw(DecodeBuffers::InPlace(&mut *buf), ignore)?;
+        Ok(&buf[..bin_len])
+    }
+
+    /// Decodes hexadecimal data, reporting a detailed error on failure.
+    ///
+    /// The analysis of invalid inputs is not constant-time.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Mutable buffer to store the decoded output
+    /// * `hex` - Hexadecimal input data to decode
+    /// * `ignore` - Optional set of characters to ignore during decoding
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - A slice of the binary buffer containing the decoded data
+    /// * `Err(Diagnostic)` - The kind of error, and the offset of the offending character
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        hex: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic> {
         let hex = hex.as_ref();
-        let bin_maxlen = bin.len();
-        let mut bin_pos = 0;
-        let mut state = false;
-        let mut c_acc = 0;
-        for &c in hex {
-            let c_num = c ^ 48;
-            let c_num0 = ((c_num as u16).wrapping_sub(10) >> 8) as u8;
-            let c_alpha = (c & !32).wrapping_sub(55);
-            let c_alpha0 = (((c_alpha as u16).wrapping_sub(10)
-                ^ ((c_alpha as u16).wrapping_sub(16)))
-                >> 8) as u8;
-            if (c_num0 | c_alpha0) == 0 {
-                match ignore {
-                    Some(ignore) if ignore.contains(&c) => continue,
-                    _ => return Err(Error::InvalidInput),
-                };
-            }
-            let c_val = (c_num0 & c_num) | (c_alpha0 & c_alpha);
-            if bin_pos >= bin_maxlen {
-                return Err(Error::Overflow);
+        match Self::validate(hex, ignore) {
+            Ok(bin_len) if bin_len > bin.len() => Err(Diagnostic::buffer_too_small(bin_len)),
+            Ok(bin_len) => {
+                Self::decode(bin, hex, ignore).map_err(|_| Diagnostic::buffer_too_small(bin_len))
             }
-            if !state {
-                c_acc = c_val << 4;
-            } else {
-                bin[bin_pos] = c_acc | c_val;
-                bin_pos += 1;
-            }
-            state = !state;
+            Err(_) => Err(locate_error(hex, ignore)),
         }
-        if state {
-            return Err(Error::InvalidInput);
+    }
+}
+
+// Not constant-time: only used to explain why an input was rejected.
+fn locate_error(hex: &[u8], ignore: Option<&[u8]>) -> Diagnostic {
+    let mut last = 0;
+    for (hex_pos, &c) in hex.iter().enumerate() {
+        if c.is_ascii_hexdigit() {
+            last = hex_pos;
+        } else if !matches!(ignore, Some(ignore) if ignore.contains(&c)) {
+            return Diagnostic::at(ErrorKind::InvalidCharacter, hex_pos);
+        }
+    }
+    Diagnostic::at(ErrorKind::InvalidLength, last)
+}
+
+fn decode_raw(mut buffers: DecodeBuffers<'_>, ignore: Option<&[u8]>) -> Result<usize, Error> {
+    let mut bin_pos = 0;
+    let mut state = false;
+    let mut c_acc = 0;
+    for hex_pos in 0..buffers.input_len() {
+        let c = buffers.input(hex_pos);
+        let (c_val, valid) = char_to_nibble(Secret::new(c));
+        if valid.expose() == 0 {
+            match ignore {
+                Some(ignore) if ignore.contains(&c) => continue,
+                _ => return Err(Error::InvalidInput),
+            };
         }
-        Ok(&bin[..bin_pos])
+        let c_val = c_val.expose();
+        if !state {
+            c_acc = c_val << 4;
+        } else {
+            buffers.write(bin_pos, c_acc | c_val)?;
+            bin_pos += 1;
+        }
+        state = !state;
+    }
+    if state {
+        return Err(Error::InvalidInput);
     }
+    Ok(bin_pos)
 }
 
 #[cfg(feature = "std")]
@@ -173,3 +365,107 @@ fn test_hex_no_std() {
     let bin2 = Hex::decode(&mut bin2, hex, None).unwrap();
     assert_eq!(bin, bin2);
 }
+
+#[test]
+fn test_hex_validate() {
+    assert_eq!(Hex::validate("01050b0F1383", None), Ok(6));
+    assert_eq!(Hex::validate("01:05:0b", Some(b":")), Ok(3));
+    assert_eq!(Hex::validate("", None), Ok(0));
+    assert_eq!(Hex::validate("01050", None), Err(Error::InvalidInput));
+    assert_eq!(Hex::validate("01:05", None), Err(Error::InvalidInput));
+    assert_eq!(Hex::validate("0g", None), Err(Error::InvalidInput));
+
+    // Odd-length inputs are invalid, even if the output buffer is full.
+    let mut bin = [0u8; 2];
+    assert_eq!(
+        Hex::decode(&mut bin, "01050", None),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        Hex::decode(&mut bin[..1], "010", None),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        Hex::decode(&mut bin[..1], "0105", None),
+        Err(Error::Overflow)
+    );
+}
+
+#[test]
+fn test_hex_diagnose() {
+    let mut bin = [0u8; 3];
+    assert_eq!(
+        Hex::diagnose(&mut bin, "01:05", None),
+        Err(Diagnostic {
+            kind: ErrorKind::InvalidCharacter,
+            offset: Some(2)
+        })
+    );
+    assert_eq!(
+        Hex::diagnose(&mut bin, "01:05:0", Some(b":")),
+        Err(Diagnostic {
+            kind: ErrorKind::InvalidLength,
+            offset: Some(6)
+        })
+    );
+    assert_eq!(
+        Hex::diagnose(&mut bin[..1], "0105", None),
+        Err(Diagnostic {
+            kind: ErrorKind::BufferTooSmall { needed: 2 },
+            offset: None
+        })
+    );
+    assert_eq!(
+        Hex::diagnose(&mut bin, "01050b", None),
+        Ok(&[1u8, 5, 11][..])
+    );
+}
+
+#[test]
+fn test_hex_in_place() {
+    use crate::EncoderExt;
+
+    let bin = [1u8, 5, 11, 15, 19, 131];
+    let mut buf = [0u8; 12];
+    buf[..6].copy_from_slice(&bin);
+    assert_eq!(Hex::encode_in_place(&mut buf, 6).unwrap(), b"01050b0f1383");
+    assert_eq!(Hex::decode_in_place(&mut buf, None).unwrap(), bin);
+    assert_eq!(
+        Hex::encode_in_place(&mut buf[..11], 6),
+        Err(Error::Overflow)
+    );
+
+    let mut buf = *b"01:05:0b:0f:13:83";
+    assert_eq!(Hex::decode_in_place(&mut buf, Some(b":")).unwrap(), bin);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hex_display() {
+    use crate::EncoderExt;
+
+    let bin: Vec<u8> = (0..=255u8).collect();
+    let hex = Hex::encode_to_string(&bin).unwrap();
+    assert_eq!(Hex::display(&bin).to_string(), hex);
+    assert_eq!(format!("{:x}", Hex::display(&bin)), hex);
+    assert_eq!(format!("{:X}", Hex::display(&bin)), hex.to_uppercase());
+    assert_eq!(format!("{:#X}", Hex::display(&[0xab, 0x01])), "0xAB01");
+    assert_eq!(format!("{:x}", Hex::display(&[])), "");
+}
+
+#[test]
+fn test_hex_mappers() {
+    for x in 0..16u8 {
+        let c = nibble_to_char(Tainted::new(x)).expose();
+        assert_eq!(c, b"0123456789abcdef"[x as usize]);
+        let (d, valid) = char_to_nibble(Tainted::new(c.to_ascii_uppercase()));
+        assert_eq!((d.expose(), valid.expose()), (x, 0xff));
+    }
+    for c in 0..=255u8 {
+        let (d, valid) = char_to_nibble(Tainted::new(c));
+        assert_eq!(valid.expose() != 0, c.is_ascii_hexdigit());
+        if c.is_ascii_hexdigit() {
+            assert_eq!(d.expose(), (c as char).to_digit(16).unwrap() as u8);
+        }
+    }
+}
@@ -0,0 +1,436 @@
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::clear;
+use crate::ct::*;
+use crate::error::*;
+
+/// Hexadecimal format with separators, grouping, prefixes and a choice of case.
+///
+/// Bytes are split into groups of `group_size` bytes. Every group starts with
+/// `prefix`, and groups are separated by the `separator` byte. A group size
+/// of `0` puts all the bytes into a single group.
+///
+/// Encoding runs in constant time relative to the input length. Decoding only
+/// accepts the exact format, and the position of every separator and prefix
+/// is derived from the input length alone, so decoding also runs in constant
+/// time relative to the input length.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::HexFormat;
+///
+/// fn example() -> Result<(), ct_codecs::Error> {
+///     let fingerprint = HexFormat::new().separator(b':').uppercase(true);
+///     let mut encoded = [0u8; 8];
+///     assert_eq!(fingerprint.encode_to_str(&mut encoded, [0xab, 0xcd, 0xef])?, "AB:CD:EF");
+///
+///     let mut bin = [0u8; 3];
+///     assert_eq!(fingerprint.decode(&mut bin, "AB:CD:EF")?, [0xab, 0xcd, 0xef]);
+///     assert!(fingerprint.decode(&mut bin, "ABC:DEF").is_err());
+///     assert!(fingerprint.decode(&mut bin, "ab:cd:ef").is_err());
+///
+///     let dump = HexFormat::new().separator(b' ').group_size(4).prefix("0x");
+///     let mut encoded = [0u8; 26];
+///     assert_eq!(
+///         dump.encode_to_str(&mut encoded, [0xde, 0xad, 0xbe, 0xef, 1, 2, 3, 4, 5])?,
+///         "0xdeadbeef 0x01020304 0x05"
+///     );
+///     Ok(())
+/// }
+/// # example().unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct HexFormat<'a> {
+    separator: Option<u8>,
+    group_size: usize,
+    prefix: &'a str,
+    uppercase: bool,
+}
+
+impl Default for HexFormat<'_> {
+    fn default() -> Self {
+        HexFormat::new()
+    }
+}
+
+impl<'a> HexFormat<'a> {
+    /// Creates a format equivalent to [`Hex`](crate::Hex): lowercase, with no
+    /// separators and no prefixes.
+    pub const fn new() -> Self {
+        HexFormat {
+            separator: None,
+            group_size: 1,
+            prefix: "",
+            uppercase: false,
+        }
+    }
+
+    /// Sets the ASCII character written between groups.
+    pub const fn separator(mut self, separator: u8) -> Self {
+        self.separator = Some(separator);
+        self
+    }
+
+    /// Sets the number of bytes per group, `0` meaning a single group.
+    pub const fn group_size(mut self, group_size: usize) -> Self {
+        self.group_size = group_size;
+        self
+    }
+
+    /// Sets the string written at the beginning of every group.
+    pub const fn prefix(mut self, prefix: &'a str) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Selects uppercase or lowercase letters.
+    pub const fn uppercase(mut self, uppercase: bool) -> Self {
+        self.uppercase = uppercase;
+        self
+    }
+
+    #[inline]
+    fn separator_len(&self) -> usize {
+        self.separator.is_some() as usize
+    }
+
+    // Number of bytes per group, given the total number of bytes.
+    #[inline]
+    fn effective_group_size(&self, bin_len: usize) -> usize {
+        match self.group_size {
+            0 => bin_len.max(1),
+            group_size => group_size,
+        }
+    }
+
+    /// Calculates the length of the encoded output for a given binary input length.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin_len` - The length of the binary input in bytes
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The required length for the encoded output
+    /// * `Err(Error::Overflow)` - If the calculation would overflow
+    pub fn encoded_len(&self, bin_len: usize) -> Result<usize, Error> {
+        if bin_len == 0 {
+            return Ok(0);
+        }
+        let group_size = self.effective_group_size(bin_len);
+        let groups = (bin_len - 1) / group_size + 1;
+        bin_len
+            .checked_mul(2)
+            .and_then(|len| len.checked_add(groups.checked_mul(self.prefix.len())?))
+            .and_then(|len| len.checked_add((groups - 1) * self.separator_len()))
+            .ok_or(Error::Overflow)
+    }
+
+    // Returns the only binary length that encodes to `encoded_len` characters.
+    fn decoded_len(&self, encoded_len: usize) -> Result<usize, Error> {
+        if encoded_len == 0 {
+            return Ok(0);
+        }
+        let (prefix_len, separator_len) = (self.prefix.len(), self.separator_len());
+        let bin_len = if self.group_size == 0 {
+            let hex_len = encoded_len
+                .checked_sub(prefix_len)
+                .ok_or(Error::InvalidInput)?;
+            hex_len / 2
+        } else {
+            let unit_len = self
+                .group_size
+                .checked_mul(2)
+                .and_then(|len| len.checked_add(prefix_len + separator_len))
+                .ok_or(Error::Overflow)?;
+            let len = encoded_len
+                .checked_add(separator_len)
+                .ok_or(Error::Overflow)?;
+            let full_groups = len / unit_len;
+            let last_group_len = (len % unit_len).saturating_sub(prefix_len + separator_len);
+            full_groups * self.group_size + last_group_len / 2
+        };
+        if bin_len == 0 || self.encoded_len(bin_len)? != encoded_len {
+            return Err(Error::InvalidInput);
+        }
+        Ok(bin_len)
+    }
+
+    #[inline]
+    fn nibble_to_char(&self, x: u8) -> u8 {
+        let alpha = if self.uppercase { b'A' } else { b'a' };
+        let x = Secret::new(x);
+        (_map_range(x, 0, 9, b'0') | _map_range(x, 10, 15, alpha)).expose()
+    }
+
+    // Returns the value of a hex digit of the selected case, and 0xff if it was valid.
+    #[inline]
+    fn char_to_nibble(&self, c: u8) -> (u8, u8) {
+        let alpha = if self.uppercase { b'A' } else { b'a' };
+        let c = Secret::new(c);
+        let x = _map_range(c, b'0', b'9', 0) | _map_range(c, alpha, alpha + 5, 10);
+        let valid = _in_range(c, b'0', b'9') | _in_range(c, alpha, alpha + 5);
+        (x.expose(), valid.expose())
+    }
+
+    /// Encodes binary data using this format.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded` - Mutable buffer to store the encoded output
+    /// * `bin` - Binary input data to encode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - A slice of the encoded buffer containing the encoded data
+    /// * `Err(Error::Overflow)` - If the output buffer is too small
+    /// * `Err(Error::InvalidInput)` - If the separator is not an ASCII character
+    pub fn encode<'t, IN: AsRef<[u8]>>(
+        &self,
+        encoded: &'t mut [u8],
+        bin: IN,
+    ) -> Result<&'t [u8], Error> {
+        let bin = bin.as_ref();
+        if matches!(self.separator, Some(separator) if !separator.is_ascii()) {
+            return Err(Error::InvalidInput);
+        }
+        let encoded_len = self.encoded_len(bin.len())?;
+        if encoded.len() < encoded_len {
+            return Err(Error::Overflow);
+        }
+        let mut pos = 0;
+        for (i, group) in bin.chunks(self.effective_group_size(bin.len())).enumerate() {
+            if let (true, Some(separator)) = (i > 0, self.separator) {
+                encoded[pos] = separator;
+                pos += 1;
+            }
+            encoded[pos..pos + self.prefix.len()].copy_from_slice(self.prefix.as_bytes());
+            pos += self.prefix.len();
+            for &x in group {
+                encoded[pos] = self.nibble_to_char(x >> 4);
+                encoded[pos + 1] = self.nibble_to_char(x & 0xf);
+                pos += 2;
+            }
+        }
+        Ok(&encoded[..encoded_len])
+    }
+
+    /// Encodes binary data and returns the result as a string slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded` - Mutable buffer to store the encoded output
+    /// * `bin` - Binary input data to encode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&str)` - A string slice containing the encoded data
+    /// * `Err(Error::Overflow)` - If the output buffer is too small
+    /// * `Err(Error::InvalidInput)` - If the separator is not an ASCII character
+    pub fn encode_to_str<'t, IN: AsRef<[u8]>>(
+        &self,
+        encoded: &'t mut [u8],
+        bin: IN,
+    ) -> Result<&'t str, Error> {
+        Ok(core::str::from_utf8(self.encode(encoded, bin)?).unwrap())
+    }
+
+    /// Encodes binary data and returns the result as a String.
+    ///
+    /// This method is only available when the `alloc` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Binary input data to encode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - A String containing the encoded data
+    /// * `Err(Error::Overflow)` - If the calculation would overflow
+    /// * `Err(Error::InvalidInput)` - If the separator is not an ASCII character
+    #[cfg(feature = "alloc")]
+    pub fn encode_to_string<IN: AsRef<[u8]>>(&self, bin: IN) -> Result<String, Error> {
+        let mut encoded = vec![0u8; self.encoded_len(bin.as_ref().len())?];
+        if let Err(e) = self.encode(&mut encoded, bin) {
+            clear(&mut encoded);
+            return Err(e);
+        }
+        Ok(String::from_utf8(encoded).unwrap())
+    }
+
+    /// Decodes data that was encoded with exactly this format.
+    ///
+    /// Separators and prefixes must be present at their expected positions
+    /// only, and letters must use the selected case.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Mutable buffer to store the decoded output
+    /// * `encoded` - Encoded input data to decode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - A slice of the binary buffer containing the decoded data
+    /// * `Err(Error::Overflow)` - If the output buffer is too small
+    /// * `Err(Error::InvalidInput)` - If the input doesn't match the format
+    pub fn decode<'t, IN: AsRef<[u8]>>(
+        &self,
+        bin: &'t mut [u8],
+        encoded: IN,
+    ) -> Result<&'t [u8], Error> {
+        let encoded = encoded.as_ref();
+        let bin_len = self.decoded_len(encoded.len())?;
+        if bin.len() < bin_len {
+            return Err(Error::Overflow);
+        }
+        let group_size = self.effective_group_size(bin_len);
+        let mut invalid = 0u8;
+        let mut pos = 0;
+        for (i, x) in bin[..bin_len].iter_mut().enumerate() {
+            if i % group_size == 0 {
+                if let (true, Some(separator)) = (i > 0, self.separator) {
+                    invalid |= encoded[pos] ^ separator;
+                    pos += 1;
+                }
+                for &c in self.prefix.as_bytes() {
+                    invalid |= encoded[pos] ^ c;
+                    pos += 1;
+                }
+            }
+            let (hi, hi_valid) = self.char_to_nibble(encoded[pos]);
+            let (lo, lo_valid) = self.char_to_nibble(encoded[pos + 1]);
+            invalid |= !(hi_valid & lo_valid);
+            *x = (hi << 4) | lo;
+            pos += 2;
+        }
+        if invalid != 0 {
+            clear(&mut bin[..bin_len]);
+            return Err(Error::InvalidInput);
+        }
+        Ok(&bin[..bin_len])
+    }
+
+    /// Decodes data that was encoded with exactly this format into a `Vec<u8>`.
+    ///
+    /// This method is only available when the `alloc` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded` - Encoded input data to decode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<u8>)` - A Vec containing the decoded binary data
+    /// * `Err(Error::InvalidInput)` - If the input doesn't match the format
+    #[cfg(feature = "alloc")]
+    pub fn decode_to_vec<IN: AsRef<[u8]>>(&self, encoded: IN) -> Result<Vec<u8>, Error> {
+        let encoded = encoded.as_ref();
+        let mut bin = vec![0u8; self.decoded_len(encoded.len())?];
+        self.decode(&mut bin, encoded)?;
+        Ok(bin)
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hex_format() {
+    let mac = HexFormat::new().separator(b'-');
+    let encoded = mac
+        .encode_to_string([0xaa, 0xbb, 0xcc, 0x01, 0x02, 0xff])
+        .unwrap();
+    assert_eq!(encoded, "aa-bb-cc-01-02-ff");
+    assert_eq!(
+        mac.decode_to_vec(&encoded).unwrap(),
+        [0xaa, 0xbb, 0xcc, 0x01, 0x02, 0xff]
+    );
+    assert_eq!(
+        mac.decode_to_vec("aabb-cc-01-02-ff-"),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        mac.decode_to_vec("aa-bb-cc-01-02-FF"),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(mac.decode_to_vec("aa:bb"), Err(Error::InvalidInput));
+    assert_eq!(mac.decode_to_vec("aa-b"), Err(Error::InvalidInput));
+    assert_eq!(mac.decode_to_vec("-"), Err(Error::InvalidInput));
+
+    let words = HexFormat::new()
+        .separator(b' ')
+        .group_size(4)
+        .prefix("0x")
+        .uppercase(true);
+    let encoded = words.encode_to_string([0xde, 0xad, 0xbe, 0xef, 1]).unwrap();
+    assert_eq!(encoded, "0xDEADBEEF 0x01");
+    assert_eq!(
+        words.decode_to_vec(&encoded).unwrap(),
+        [0xde, 0xad, 0xbe, 0xef, 1]
+    );
+    assert_eq!(
+        words.decode_to_vec("0xDEAD 0xBEEF01"),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        words.decode_to_vec("0XDEADBEEF 0x01"),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        words.decode_to_vec("0xDEADBEEF 0x"),
+        Err(Error::InvalidInput)
+    );
+
+    let single = HexFormat::new().group_size(0).prefix("0x");
+    assert_eq!(single.encode_to_string([1, 2, 3]).unwrap(), "0x010203");
+    assert_eq!(single.encode_to_string([]).unwrap(), "");
+    assert_eq!(single.decode_to_vec("0x010203").unwrap(), [1, 2, 3]);
+    assert!(single.decode_to_vec("").unwrap().is_empty());
+    assert_eq!(single.decode_to_vec("0x"), Err(Error::InvalidInput));
+
+    let mut bin = [0u8; 2];
+    assert_eq!(mac.decode(&mut bin, "01-02-03"), Err(Error::Overflow));
+    assert_eq!(
+        HexFormat::new().separator(0xe9).encode_to_string([1, 2]),
+        Err(Error::InvalidInput)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hex_format_roundtrip() {
+    use crate::{Encoder, Hex};
+
+    let bin: Vec<u8> = (0..40u32).map(|i| (i * 71) as u8).collect();
+    for group_size in 0..6 {
+        for &(separator, prefix) in &[
+            (None, ""),
+            (Some(b':'), ""),
+            (Some(b' '), "0x"),
+            (None, "$"),
+        ] {
+            for &uppercase in &[false, true] {
+                let mut format = HexFormat::new()
+                    .group_size(group_size)
+                    .prefix(prefix)
+                    .uppercase(uppercase);
+                if let Some(separator) = separator {
+                    format = format.separator(separator);
+                }
+                for len in 0..bin.len() {
+                    let encoded = format.encode_to_string(&bin[..len]).unwrap();
+                    assert_eq!(encoded.len(), format.encoded_len(len).unwrap());
+                    assert_eq!(format.decode_to_vec(&encoded).unwrap(), &bin[..len]);
+                    if !encoded.is_empty() {
+                        assert!(format.decode_to_vec(&encoded[1..]).is_err());
+                        assert!(format.decode_to_vec(&encoded[..encoded.len() - 1]).is_err());
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(
+        HexFormat::new().encode_to_string(&bin).unwrap(),
+        Hex::encode_to_string(&bin).unwrap()
+    );
+}
@@ -0,0 +1,251 @@
+//! Canonical `xxd`-style hex dumps, and parsing them back.
+//!
+//! Every row holds 16 bytes: the offset, the hexadecimal column in groups of
+//! two bytes, and an ASCII gutter in which non-printable bytes are shown as
+//! dots. The output is identical to the one of the `xxd` command.
+//!
+//! Hexadecimal digits and the ASCII gutter are computed without branches or
+//! table lookups depending on the data.
+//!
+//! # Examples
+//!
+//! ```
+//! use ct_codecs::hexdump;
+//!
+//! fn example() -> Result<(), ct_codecs::Error> {
+//!     let mut dump = String::new();
+//!     hexdump::write(&mut dump, b"Hello, world!\n").unwrap();
+//!     assert_eq!(
+//!         dump,
+//!         "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a       Hello, world!.\n"
+//!     );
+//!
+//!     let mut bin = [0u8; 14];
+//!     assert_eq!(hexdump::parse(&mut bin, &dump)?, b"Hello, world!\n");
+//!     Ok(())
+//! }
+//! # example().unwrap();
+//! ```
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use crate::ct::*;
+use crate::error::*;
+use crate::{clear, Decoder, Encoder, Hex};
+
+const ROW_LEN: usize = 16;
+
+// Offset (up to 16 digits), ": ", hex column, " ", ASCII gutter, newline.
+const LINE_MAXLEN: usize = 16 + 2 + ROW_LEN * 5 / 2 + 1 + ROW_LEN + 1;
+
+// Replaces bytes outside of the printable ASCII range with '.'.
+#[inline]
+fn to_printable(c: u8) -> u8 {
+    let c = Secret::new(c);
+    _select(_in_range(c, 0x20, 0x7e), c, Secret::new(b'.')).expose()
+}
+
+// Builds the dump row by row in a stack buffer, passing every row to `f`.
+fn write_rows<E, F: FnMut(&str) -> Result<(), E>>(bin: &[u8], mut f: F) -> Result<(), E> {
+    let mut line = [0u8; LINE_MAXLEN];
+    let mut hex = [0u8; 2 * ROW_LEN];
+    let mut res = Ok(());
+    for (i, row) in bin.chunks(ROW_LEN).enumerate() {
+        let offset = (i * ROW_LEN) as u64;
+        let mut pos = if offset <= u32::MAX as u64 {
+            Hex::encode(&mut line, (offset as u32).to_be_bytes())
+        } else {
+            Hex::encode(&mut line, offset.to_be_bytes())
+        }
+        .unwrap()
+        .len();
+        line[pos..pos + 2].copy_from_slice(b": ");
+        pos += 2;
+        let hex_len = Hex::encode(&mut hex, row).unwrap().len();
+        for j in 0..ROW_LEN {
+            if 2 * j < hex_len {
+                line[pos..pos + 2].copy_from_slice(&hex[2 * j..2 * j + 2]);
+            } else {
+                line[pos..pos + 2].copy_from_slice(b"  ");
+            }
+            pos += 2;
+            if j % 2 == 1 {
+                line[pos] = b' ';
+                pos += 1;
+            }
+        }
+        line[pos] = b' ';
+        pos += 1;
+        for &c in row {
+            line[pos] = to_printable(c);
+            pos += 1;
+        }
+        line[pos] = b'\n';
+        pos += 1;
+        res = f(core::str::from_utf8(&line[..pos]).unwrap());
+        if res.is_err() {
+            break;
+        }
+    }
+    clear(&mut line);
+    clear(&mut hex);
+    res
+}
+
+/// Writes a hex dump of binary data to a formatter or a `String`.
+///
+/// # Arguments
+///
+/// * `out` - Destination of the dump
+/// * `bin` - Binary data to dump
+pub fn write<W: fmt::Write + ?Sized>(out: &mut W, bin: &[u8]) -> fmt::Result {
+    write_rows(bin, |line| out.write_str(line))
+}
+
+/// Writes a hex dump of binary data to a file, a socket or any other writer.
+///
+/// This function is only available when the `std` feature is enabled.
+///
+/// # Arguments
+///
+/// * `out` - Destination of the dump
+/// * `bin` - Binary data to dump
+#[cfg(feature = "std")]
+pub fn write_io<W: std::io::Write + ?Sized>(out: &mut W, bin: &[u8]) -> std::io::Result<()> {
+    write_rows(bin, |line| out.write_all(line.as_bytes()))
+}
+
+// Decodes the hexadecimal column of a row, returning the number of bytes written.
+fn parse_row(bin: &mut [u8], row: &[u8]) -> Result<usize, Error> {
+    let colon = row
+        .iter()
+        .position(|&c| c == b':')
+        .ok_or(Error::InvalidInput)?;
+    let mut rest = &row[colon + 1..];
+    let mut bin_pos = 0;
+    while bin_pos < ROW_LEN {
+        match rest {
+            [b' ', b' ', ..] | [b' '] | [] => break,
+            [b' ', tail @ ..] => rest = tail,
+            _ => return Err(Error::InvalidInput),
+        }
+        let group_len = rest.iter().position(|&c| c == b' ').unwrap_or(rest.len());
+        let group = Hex::decode(&mut bin[bin_pos..], &rest[..group_len], None)?;
+        bin_pos += group.len();
+        rest = &rest[group_len..];
+    }
+    if bin_pos > ROW_LEN {
+        return Err(Error::InvalidInput);
+    }
+    Ok(bin_pos)
+}
+
+/// Parses a hex dump back into binary data, like `xxd -r`.
+///
+/// Offsets and the ASCII gutter are ignored: rows are decoded sequentially.
+/// The hexadecimal column ends at the first double space, or after 16 bytes.
+/// Empty lines are skipped, and both lowercase and uppercase digits are accepted.
+///
+/// # Arguments
+///
+/// * `bin` - Mutable buffer to store the decoded output
+/// * `dump` - Hex dump to parse
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the binary buffer containing the decoded data
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+/// * `Err(Error::InvalidInput)` - If a row is not a valid hex dump row
+pub fn parse<IN: AsRef<[u8]>>(bin: &mut [u8], dump: IN) -> Result<&[u8], Error> {
+    let mut bin_pos = 0;
+    for row in dump.as_ref().split(|&c| c == b'\n') {
+        let row = row.strip_suffix(b"\r").unwrap_or(row);
+        if row.is_empty() {
+            continue;
+        }
+        match parse_row(&mut bin[bin_pos..], row) {
+            Ok(row_len) => bin_pos += row_len,
+            Err(e) => {
+                clear(bin);
+                return Err(e);
+            }
+        }
+    }
+    Ok(&bin[..bin_pos])
+}
+
+/// Parses a hex dump back into binary data, returned as a `Vec<u8>`.
+///
+/// This function is only available when the `alloc` feature is enabled.
+///
+/// # Arguments
+///
+/// * `dump` - Hex dump to parse
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` - A Vec containing the decoded binary data
+/// * `Err(Error::InvalidInput)` - If a row is not a valid hex dump row
+#[cfg(feature = "alloc")]
+pub fn parse_to_vec<IN: AsRef<[u8]>>(dump: IN) -> Result<Vec<u8>, Error> {
+    let dump = dump.as_ref();
+    let mut bin = vec![0u8; dump.len() / 2];
+    let bin_len = match parse(&mut bin, dump) {
+        Ok(bin) => bin.len(),
+        Err(e) => {
+            clear(&mut bin);
+            return Err(e);
+        }
+    };
+    bin.truncate(bin_len);
+    Ok(bin)
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hexdump() {
+    let bin: Vec<u8> = (0..40u8).map(|i| i.wrapping_mul(0x3b)).collect();
+    let mut dump = String::new();
+    write(&mut dump, &bin).unwrap();
+    assert_eq!(
+        dump,
+        "00000000: 003b 76b1 ec27 629d d813 4e89 c4ff 3a75  .;v..'b...N...:u\n\
+         00000010: b0eb 2661 9cd7 124d 88c3 fe39 74af ea25  ..&a...M...9t..%\n\
+         00000020: 609b d611 4c87 c2fd                      `...L...\n"
+    );
+    assert_eq!(parse_to_vec(&dump).unwrap(), bin);
+
+    let mut io_dump = Vec::new();
+    write_io(&mut io_dump, &bin).unwrap();
+    assert_eq!(io_dump, dump.as_bytes());
+
+    let mut empty = String::new();
+    write(&mut empty, &[]).unwrap();
+    assert_eq!(empty, "");
+    assert!(parse_to_vec("").unwrap().is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hexdump_parse() {
+    // Offsets are ignored, and the ASCII gutter may look like hex digits.
+    let dump = "00000010: 6162 6364 6566 6768 6162 6364 6566 6768  abcdefghabcdefgh\r\n\
+                \n\
+                00000000: 4142 43                                  ABC";
+    assert_eq!(parse_to_vec(dump).unwrap(), b"abcdefghabcdefghABC");
+    assert_eq!(parse_to_vec("0: 4142 43  ab ab").unwrap(), b"ABC");
+    assert_eq!(parse_to_vec("0: 4142 43\n1: 44").unwrap(), b"ABCD");
+    assert_eq!(parse_to_vec("0: 4142 4").unwrap_err(), Error::InvalidInput);
+    assert_eq!(parse_to_vec("4142 43").unwrap_err(), Error::InvalidInput);
+    assert_eq!(parse_to_vec("0:4142").unwrap_err(), Error::InvalidInput);
+    assert_eq!(parse_to_vec("0: 41xx").unwrap_err(), Error::InvalidInput);
+    assert_eq!(
+        parse_to_vec("0: 0001 0203 0405 0607 0809 0a0b 0c0d 0e0f 1011").unwrap(),
+        (0..16).collect::<Vec<u8>>()
+    );
+    let mut bin = [0u8; 2];
+    assert_eq!(parse(&mut bin, "0: 4142 43"), Err(Error::Overflow));
+}
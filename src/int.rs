@@ -0,0 +1,441 @@
+//! Encoding of unsigned integers, as big-endian byte strings.
+//!
+//! Integers can be encoded with any codec of the crate, either with a fixed
+//! width (every byte of the integer type or of the given big-endian byte
+//! string), or with a minimal width (leading zero bytes stripped, as in the
+//! `n` and `e` members of a JSON Web Key).
+//!
+//! Ethereum-style quantities, `0x`-prefixed hexadecimal numbers without
+//! leading zero digits, are also supported.
+//!
+//! Fixed-width encoding and decoding run in constant time. The minimal width
+//! and quantity formats reveal the magnitude of the value by design, through
+//! the encoded length, so they are not constant-time.
+//!
+//! # Examples
+//!
+//! ```
+//! use ct_codecs::int::{self, Width};
+//! use ct_codecs::Base64UrlSafeNoPadding;
+//!
+//! fn example() -> Result<(), ct_codecs::Error> {
+//!     let mut encoded = [0u8; 24];
+//!     let e = int::encode_uint::<Base64UrlSafeNoPadding, u32>(&mut encoded, 65537, Width::Minimal)?;
+//!     assert_eq!(e, b"AQAB");
+//!     assert_eq!(int::decode_uint::<Base64UrlSafeNoPadding, u32>("AQAB", Width::Minimal)?, 65537);
+//!
+//!     let quantity = int::encode_quantity(&mut encoded, 1024u64)?;
+//!     assert_eq!(quantity, "0x400");
+//!     assert_eq!(int::decode_quantity::<u64>("0x400")?, 1024);
+//!     Ok(())
+//! }
+//! # example().unwrap();
+//! ```
+
+use crate::error::*;
+use crate::{clear, Decoder, Encoder, Hex};
+
+/// Width of an encoded integer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Width {
+    /// Every byte is encoded, including leading zeros. Constant-time.
+    Fixed,
+    /// Leading zero bytes are stripped, keeping at least one byte. When
+    /// decoding, leading zero bytes are rejected.
+    Minimal,
+}
+
+/// Unsigned integer types that can be encoded.
+pub trait Uint: Copy {
+    /// Big-endian representation of the integer.
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default;
+
+    /// Returns the big-endian representation of the integer.
+    fn to_be_bytes(self) -> Self::Bytes;
+
+    /// Creates an integer from its big-endian representation.
+    fn from_be_bytes(bytes: Self::Bytes) -> Self;
+}
+
+macro_rules! impl_uint {
+    ($($t:ty),*) => {
+        $(
+            impl Uint for $t {
+                type Bytes = [u8; core::mem::size_of::<$t>()];
+
+                #[inline]
+                fn to_be_bytes(self) -> Self::Bytes {
+                    <$t>::to_be_bytes(self)
+                }
+
+                #[inline]
+                fn from_be_bytes(bytes: Self::Bytes) -> Self {
+                    <$t>::from_be_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_uint!(u8, u16, u32, u64, u128);
+
+// Returns `be` without its leading zero bytes, keeping at least one byte.
+fn strip_leading_zeros(be: &[u8]) -> &[u8] {
+    let skip = be
+        .iter()
+        .take(be.len().saturating_sub(1))
+        .take_while(|&&x| x == 0)
+        .count();
+    &be[skip..]
+}
+
+/// Encodes a big-endian integer stored as a byte string.
+///
+/// # Arguments
+///
+/// * `encoded` - Mutable buffer to store the encoded output
+/// * `be` - Big-endian integer to encode
+/// * `width` - Whether leading zero bytes are kept
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the encoded buffer containing the encoded data
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+pub fn encode_be<'t, E: Encoder>(
+    encoded: &'t mut [u8],
+    be: &[u8],
+    width: Width,
+) -> Result<&'t [u8], Error> {
+    match width {
+        Width::Fixed => E::encode(encoded, be),
+        Width::Minimal => E::encode(encoded, strip_leading_zeros(be)),
+    }
+}
+
+/// Decodes a big-endian integer into a byte string.
+///
+/// With [`Width::Fixed`], the decoded data must fill `bin` exactly. With
+/// [`Width::Minimal`], the decoded data must be at least one byte long, and
+/// must not start with a zero byte unless it is a single zero byte.
+///
+/// # Arguments
+///
+/// * `bin` - Mutable buffer to store the decoded output
+/// * `encoded` - Encoded integer to decode
+/// * `width` - Width of the encoded integer
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the binary buffer containing the big-endian integer
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+/// * `Err(Error::InvalidInput)` - If the input is invalid or doesn't have the expected width
+pub fn decode_be<D: Decoder, IN: AsRef<[u8]>>(
+    bin: &mut [u8],
+    encoded: IN,
+    width: Width,
+) -> Result<&[u8], Error> {
+    let be_len = D::decode(&mut *bin, encoded, None)?.len();
+    let valid = match width {
+        Width::Fixed => be_len == bin.len(),
+        Width::Minimal => be_len == 1 || (be_len > 1 && bin[0] != 0),
+    };
+    if !valid {
+        clear(&mut bin[..be_len]);
+        return Err(Error::InvalidInput);
+    }
+    Ok(&bin[..be_len])
+}
+
+/// Encodes an unsigned integer.
+///
+/// # Arguments
+///
+/// * `encoded` - Mutable buffer to store the encoded output
+/// * `x` - Integer to encode
+/// * `width` - Whether leading zero bytes are kept
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the encoded buffer containing the encoded data
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+pub fn encode_uint<E: Encoder, T: Uint>(
+    encoded: &mut [u8],
+    x: T,
+    width: Width,
+) -> Result<&[u8], Error> {
+    let mut be = x.to_be_bytes();
+    let res = encode_be::<E>(encoded, be.as_ref(), width);
+    clear(be.as_mut());
+    res
+}
+
+/// Decodes an unsigned integer.
+///
+/// With [`Width::Fixed`], the input must encode exactly as many bytes as the
+/// integer type. With [`Width::Minimal`], it can encode fewer bytes, but no
+/// leading zero bytes.
+///
+/// # Arguments
+///
+/// * `encoded` - Encoded integer to decode
+/// * `width` - Width of the encoded integer
+///
+/// # Returns
+///
+/// * `Ok(T)` - The decoded integer
+/// * `Err(Error::Overflow)` - If the value doesn't fit in the integer type
+/// * `Err(Error::InvalidInput)` - If the input is invalid or doesn't have the expected width
+pub fn decode_uint<D: Decoder, T: Uint>(
+    encoded: impl AsRef<[u8]>,
+    width: Width,
+) -> Result<T, Error> {
+    let mut be = T::Bytes::default();
+    let be_len = decode_be::<D, _>(be.as_mut(), encoded, width)?.len();
+    let bytes = be.as_mut();
+    let shift = bytes.len() - be_len;
+    bytes.copy_within(..be_len, shift);
+    bytes[..shift].iter_mut().for_each(|x| *x = 0);
+    Ok(T::from_be_bytes(be))
+}
+
+/// Encodes a big-endian integer as an Ethereum quantity.
+///
+/// The output is `0x` followed by lowercase hexadecimal digits, without
+/// leading zeros. Zero is encoded as `0x0`.
+///
+/// # Arguments
+///
+/// * `encoded` - Mutable buffer to store the encoded output
+/// * `be` - Big-endian integer to encode
+///
+/// # Returns
+///
+/// * `Ok(&str)` - A string slice containing the quantity
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+pub fn encode_quantity_be<'t>(encoded: &'t mut [u8], be: &[u8]) -> Result<&'t str, Error> {
+    let be = match strip_leading_zeros(be) {
+        [] => &[0u8][..],
+        be => be,
+    };
+    // The first byte is encoded separately, so that its leading zero digit can be skipped.
+    let skip = (be[0] >> 4 == 0) as usize;
+    let quantity_len = Hex::encoded_len(be.len())?
+        .checked_add(2 - skip)
+        .ok_or(Error::Overflow)?;
+    let quantity = encoded.get_mut(..quantity_len).ok_or(Error::Overflow)?;
+    let mut first = [0u8; 2];
+    Hex::encode(&mut first, &be[..1])?;
+    quantity[..2].copy_from_slice(b"0x");
+    quantity[2..4 - skip].copy_from_slice(&first[skip..]);
+    clear(&mut first);
+    Hex::encode(&mut quantity[4 - skip..], &be[1..])?;
+    Ok(core::str::from_utf8(quantity).unwrap())
+}
+
+/// Decodes an Ethereum quantity into a big-endian integer filling `bin`.
+///
+/// The input must start with `0x` and have at least one digit, and no
+/// leading zero digits unless the quantity is `0x0`.
+///
+/// # Arguments
+///
+/// * `bin` - Mutable buffer to store the big-endian integer, padded with leading zeros
+/// * `encoded` - Quantity to decode
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - The big-endian integer, as long as `bin`
+/// * `Err(Error::Overflow)` - If the value doesn't fit in `bin`
+/// * `Err(Error::InvalidInput)` - If the input is not a valid quantity
+#[allow(clippy::manual_div_ceil)]
+pub fn decode_quantity_be<IN: AsRef<[u8]>>(bin: &mut [u8], encoded: IN) -> Result<&[u8], Error> {
+    let digits = match encoded.as_ref() {
+        [b'0', b'x', digits @ ..] => digits,
+        _ => return Err(Error::InvalidInput),
+    };
+    match digits {
+        [] => return Err(Error::InvalidInput),
+        [b'0', _, ..] => return Err(Error::InvalidInput),
+        _ => {}
+    }
+    let be_len = (digits.len() + 1) / 2;
+    if be_len > bin.len() {
+        return Err(Error::Overflow);
+    }
+    let (zeros, be) = bin.split_at_mut(bin.len() - be_len);
+    zeros.iter_mut().for_each(|x| *x = 0);
+    let res = if digits.len() % 2 == 1 {
+        Hex::decode(&mut be[..1], [b'0', digits[0]], None)
+            .map(|_| ())
+            .and_then(|()| Hex::decode(&mut be[1..], &digits[1..], None).map(|_| ()))
+    } else {
+        Hex::decode(be, digits, None).map(|_| ())
+    };
+    if let Err(e) = res {
+        clear(bin);
+        return Err(e);
+    }
+    Ok(bin)
+}
+
+/// Encodes an unsigned integer as an Ethereum quantity.
+///
+/// # Arguments
+///
+/// * `encoded` - Mutable buffer to store the encoded output
+/// * `x` - Integer to encode
+///
+/// # Returns
+///
+/// * `Ok(&str)` - A string slice containing the quantity
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+pub fn encode_quantity<T: Uint>(encoded: &mut [u8], x: T) -> Result<&str, Error> {
+    encode_quantity_be(encoded, x.to_be_bytes().as_ref())
+}
+
+/// Decodes an Ethereum quantity into an unsigned integer.
+///
+/// # Arguments
+///
+/// * `encoded` - Quantity to decode
+///
+/// # Returns
+///
+/// * `Ok(T)` - The decoded integer
+/// * `Err(Error::Overflow)` - If the value doesn't fit in the integer type
+/// * `Err(Error::InvalidInput)` - If the input is not a valid quantity
+pub fn decode_quantity<T: Uint>(encoded: impl AsRef<[u8]>) -> Result<T, Error> {
+    let mut be = T::Bytes::default();
+    decode_quantity_be(be.as_mut(), encoded)?;
+    Ok(T::from_be_bytes(be))
+}
+
+#[test]
+fn test_int_uint() {
+    use crate::{Base64UrlSafeNoPadding, Hex};
+
+    let mut encoded = [0u8; 64];
+    let e = encode_uint::<Base64UrlSafeNoPadding, u32>(&mut encoded, 65537, Width::Minimal);
+    assert_eq!(e.unwrap(), b"AQAB");
+    let e = encode_uint::<Base64UrlSafeNoPadding, u32>(&mut encoded, 65537, Width::Fixed);
+    assert_eq!(e.unwrap(), b"AAEAAQ");
+    let x = encode_uint::<Hex, u64>(&mut encoded, 0, Width::Minimal);
+    assert_eq!(x.unwrap(), b"00");
+    let x = encode_uint::<Hex, u16>(&mut encoded, 0xabc, Width::Fixed);
+    assert_eq!(x.unwrap(), b"0abc");
+
+    assert_eq!(
+        decode_uint::<Base64UrlSafeNoPadding, u32>("AQAB", Width::Minimal),
+        Ok(65537)
+    );
+    assert_eq!(
+        decode_uint::<Base64UrlSafeNoPadding, u32>("AAEAAQ", Width::Fixed),
+        Ok(65537)
+    );
+    assert_eq!(
+        decode_uint::<Base64UrlSafeNoPadding, u32>("AQAB", Width::Fixed),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        decode_uint::<Base64UrlSafeNoPadding, u32>("AAEAAQ", Width::Minimal),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(decode_uint::<Hex, u8>("00", Width::Minimal), Ok(0));
+    assert_eq!(
+        decode_uint::<Hex, u8>("", Width::Minimal),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        decode_uint::<Hex, u16>("010000", Width::Minimal),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        decode_uint::<Hex, u128>("0102030405060708090a0b0c0d0e0f10", Width::Fixed),
+        Ok(0x0102030405060708090a0b0c0d0e0f10)
+    );
+
+    for &x in &[0u64, 1, 0xff, 0x100, 0xdead_beef, u64::MAX] {
+        for &width in &[Width::Fixed, Width::Minimal] {
+            let encoded =
+                encode_uint::<Base64UrlSafeNoPadding, u64>(&mut encoded, x, width).unwrap();
+            assert_eq!(
+                decode_uint::<Base64UrlSafeNoPadding, u64>(encoded, width),
+                Ok(x)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_int_be() {
+    use crate::Base64;
+
+    let mut encoded = [0u8; 16];
+    let n = encode_be::<Base64>(&mut encoded, &[0, 0, 0xfb, 0xff], Width::Minimal).unwrap();
+    assert_eq!(n, b"+/8=");
+    let mut bin = [0xaau8; 4];
+    assert_eq!(
+        decode_be::<Base64, _>(&mut bin, "+/8=", Width::Minimal),
+        Ok(&[0xfb, 0xff][..])
+    );
+    assert_eq!(
+        decode_be::<Base64, _>(&mut bin, "+/8=", Width::Fixed),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(bin[..2], [0, 0]);
+    assert_eq!(
+        decode_be::<Base64, _>(&mut bin, "AAD7/w==", Width::Fixed),
+        Ok(&[0, 0, 0xfb, 0xff][..])
+    );
+}
+
+#[test]
+fn test_int_quantity() {
+    let mut encoded = [0u8; 34];
+    assert_eq!(encode_quantity(&mut encoded, 0u64), Ok("0x0"));
+    assert_eq!(encode_quantity(&mut encoded, 0x41u8), Ok("0x41"));
+    assert_eq!(encode_quantity(&mut encoded, 1024u32), Ok("0x400"));
+    assert_eq!(
+        encode_quantity(&mut encoded, u128::MAX),
+        Ok("0xffffffffffffffffffffffffffffffff")
+    );
+    assert_eq!(encode_quantity_be(&mut encoded, &[]), Ok("0x0"));
+    assert_eq!(
+        encode_quantity(&mut encoded[..4], 1024u32),
+        Err(Error::Overflow)
+    );
+
+    // Exactly-sized output buffers.
+    for (x, quantity) in [
+        (0u64, "0x0"),
+        (0xf, "0xf"),
+        (0x10, "0x10"),
+        (0x400, "0x400"),
+        (0x1000, "0x1000"),
+        (u64::MAX, "0xffffffffffffffff"),
+    ] {
+        let encoded = &mut encoded[..quantity.len()];
+        assert_eq!(encode_quantity(encoded, x), Ok(quantity));
+        assert_eq!(
+            encode_quantity(&mut encoded[..quantity.len() - 1], x),
+            Err(Error::Overflow)
+        );
+    }
+
+    assert_eq!(decode_quantity::<u64>("0x0"), Ok(0));
+    assert_eq!(decode_quantity::<u64>("0x41"), Ok(0x41));
+    assert_eq!(decode_quantity::<u64>("0x400"), Ok(1024));
+    assert_eq!(
+        decode_quantity::<u128>("0xffffffffffffffffffffffffffffffff"),
+        Ok(u128::MAX)
+    );
+    assert_eq!(decode_quantity::<u64>("0x"), Err(Error::InvalidInput));
+    assert_eq!(decode_quantity::<u64>("0x0400"), Err(Error::InvalidInput));
+    assert_eq!(decode_quantity::<u64>("0x00"), Err(Error::InvalidInput));
+    assert_eq!(decode_quantity::<u64>("400"), Err(Error::InvalidInput));
+    assert_eq!(decode_quantity::<u64>("0x4g0"), Err(Error::InvalidInput));
+    assert_eq!(decode_quantity::<u8>("0x100"), Err(Error::Overflow));
+
+    let mut bin = [0xaau8; 3];
+    assert_eq!(decode_quantity_be(&mut bin, "0x400"), Ok(&[0, 4, 0][..]));
+}
@@ -8,9 +8,10 @@
 //! - **Constant-time implementation** for cryptographic applications where timing attacks are a concern
 //! - **Strict validation** ensuring encoded strings are not malleable and use canonical alphabets by default
 //! - **Multiple variants** of Base64: standard, URL-safe, with and without padding
+//! - **Mixed-alphabet Base64 decoding** with an optional canonical-alphabet check
 //! - **Multiple variants** of Base32: standard and hexadecimal alphabets, with and without padding
 //! - **Character filtering** for ignoring specific characters during decoding (like whitespace)
-//! - **Zero dependencies** and **`no_std` compatible**
+//! - **No required dependencies**; optional `zeroize`/`serde` features, and **`no_std` compatible**
 //! - **Memory safety** with `#![forbid(unsafe_code)]`
 //!
 //! ## Usage Examples
@@ -107,20 +108,55 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
 mod base32;
 mod base64;
+mod buffer;
+pub mod ct;
+mod encoded;
 mod error;
 mod hex;
+mod hex_format;
+pub mod hexdump;
+pub mod int;
+mod literal;
+pub mod padding;
+mod transcode;
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+#[cfg(feature = "serde")]
+pub mod serde;
 
 pub use base32::*;
 pub use base64::*;
+pub use encoded::*;
 pub use error::*;
 pub use hex::*;
+pub use hex_format::*;
+pub use transcode::*;
+
+#[cfg(feature = "zeroize")]
+pub use zeroize::Zeroizing;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::literal::decoded_len;
+}
 
 /// Trait for encoding binary data into text representations.
 ///
 /// Implementors of this trait provide constant-time encoding operations
 /// for a specific encoding format (Base64, Hex, etc.).
+///
+/// Only [`encoded_len`](Encoder::encoded_len) and [`encode`](Encoder::encode)
+/// have to be implemented. The encoders of this crate also implement
+/// [`EncoderExt`].
 pub trait Encoder {
     /// Calculates the length of the encoded output for a given binary input length.
     ///
@@ -164,7 +200,7 @@ pub trait Encoder {
 
     /// Encodes binary data and returns the result as a String.
     ///
-    /// This method is only available when the `std` feature is enabled.
+    /// This method is only available when the `alloc` feature is enabled.
     ///
     /// # Arguments
     ///
@@ -174,22 +210,164 @@ pub trait Encoder {
     ///
     /// * `Ok(String)` - A String containing the encoded data
     /// * `Err(Error::Overflow)` - If the calculation would overflow
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     fn encode_to_string<IN: AsRef<[u8]>>(bin: IN) -> Result<String, Error> {
         let mut encoded = vec![0u8; Self::encoded_len(bin.as_ref().len())?];
-        let encoded_len = Self::encode(&mut encoded, bin)?.len();
+        let encoded_len = match Self::encode(&mut encoded, bin) {
+            Ok(encoded) => encoded.len(),
+            Err(e) => {
+                clear(&mut encoded);
+                return Err(e);
+            }
+        };
         encoded.truncate(encoded_len);
         Ok(String::from_utf8(encoded).unwrap())
     }
+
+    /// Encodes binary data and returns the result as a String that is wiped when dropped.
+    ///
+    /// This method is only available when the `alloc` and `zeroize` features are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Binary input data to encode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Zeroizing<String>)` - A String containing the encoded data
+    /// * `Err(Error::Overflow)` - If the calculation would overflow
+    #[cfg(all(feature = "alloc", feature = "zeroize"))]
+    fn encode_to_secret<IN: AsRef<[u8]>>(bin: IN) -> Result<Zeroizing<String>, Error> {
+        let mut encoded = Zeroizing::new(vec![0u8; Self::encoded_len(bin.as_ref().len())?]);
+        let encoded_len = Self::encode(&mut encoded, bin)?.len();
+        encoded.truncate(encoded_len);
+        let encoded = core::mem::take(&mut *encoded);
+        Ok(Zeroizing::new(String::from_utf8(encoded).unwrap()))
+    }
+}
+
+/// Additional encoding operations, implemented by every encoder of the crate.
+///
+/// These encode in chunks through a small stack buffer. The chunks are sized
+/// for the groups of bytes of the encoders of this crate, so that only the
+/// last one can be padded, so they are not part of [`Encoder`], which can be
+/// implemented by other crates. This trait is sealed: it cannot be implemented
+/// outside of this crate.
+pub trait EncoderExt: Encoder + sealed::Sealed {
+    /// Encodes binary data over itself.
+    ///
+    /// The first `bin_len` bytes of `buf` are the binary data to encode.
+    /// The encoded output is written over them, working backwards from the end,
+    /// so `buf` must be at least [`encoded_len(bin_len)`](Encoder::encoded_len)
+    /// bytes long. Only a small, fixed-size stack buffer is used, and it is
+    /// cleared before returning.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer starting with the binary data, overwritten with the encoded data
+    /// * `bin_len` - The length of the binary data
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - A slice of the buffer containing the encoded data
+    /// * `Err(Error::Overflow)` - If the buffer is too small
+    fn encode_in_place(buf: &mut [u8], bin_len: usize) -> Result<&[u8], Error>
+    where
+        Self: Sized,
+    {
+        transcode::encode_in_place::<Self>(buf, bin_len)
+    }
+
+    /// Returns a value that encodes binary data when it is formatted.
+    ///
+    /// Nothing is allocated: the data is encoded in small chunks, through a
+    /// stack buffer, straight into the formatter. This is useful to log or
+    /// `write!` encoded data in `no_std` environments.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Binary input data to encode
+    ///
+    /// # Returns
+    ///
+    /// * `EncodedDisplay` - A value implementing `core::fmt::Display`
+    fn display(bin: &[u8]) -> EncodedDisplay<'_, Self>
+    where
+        Self: Sized,
+    {
+        EncodedDisplay::new(bin)
+    }
+
+    /// Encodes binary data and appends the result to an existing String.
+    ///
+    /// Exactly [`encoded_len`](Encoder::encoded_len) bytes are reserved, and the
+    /// data is encoded in small chunks through a stack buffer that is cleared
+    /// afterwards. On error, `encoded` is left unchanged.
+    /// This method is only available when the `alloc` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded` - String to append the encoded data to
+    /// * `bin` - Binary input data to encode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the encoded data was appended
+    /// * `Err(Error::Overflow)` - If the calculation would overflow
+    #[cfg(feature = "alloc")]
+    fn encode_append<IN: AsRef<[u8]>>(encoded: &mut String, bin: IN) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        let bin = bin.as_ref();
+        encoded.reserve_exact(Self::encoded_len(bin.len())?);
+        let previous_len = encoded.len();
+        let res = transcode::encode_chunks::<Self, _>(bin, |chunk| {
+            encoded.push_str(chunk);
+            Ok(())
+        });
+        if res.is_err() {
+            // Overwrite the chunks that were already appended with zeros,
+            // within the same allocation, before removing them.
+            let appended_len = encoded.len() - previous_len;
+            encoded.truncate(previous_len);
+            (0..appended_len).for_each(|_| encoded.push('\0'));
+            encoded.truncate(previous_len);
+            return Err(Error::Overflow);
+        }
+        Ok(())
+    }
 }
 
+impl<E: Encoder + sealed::Sealed> EncoderExt for E {}
+
 /// Trait for decoding text representations back into binary data.
 ///
 /// Implementors of this trait provide constant-time decoding operations
 /// for a specific encoding format (Base64, Hex, etc.). By default,
 /// decoders require the canonical alphabet for the selected variant;
 /// only bytes explicitly listed in `ignore` are skipped.
+///
+/// Only [`decode`](Decoder::decode) has to be implemented. The decoders of
+/// this crate also implement [`DecoderExt`].
 pub trait Decoder {
+    /// Calculates the maximum length of the decoded output for a given encoded input length.
+    ///
+    /// Padding and ignored characters don't decode to anything, so the
+    /// decoded data can be shorter. The default implementation returns
+    /// `encoded_len`, as decoded data is never longer than encoded data.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded_len` - The length of the encoded input in bytes
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The maximum length of the decoded output
+    fn max_decoded_len(encoded_len: usize) -> usize {
+        encoded_len
+    }
+
     /// Decodes text data back into its binary representation.
     ///
     /// This method rejects non-canonical encodings, invalid padding,
@@ -215,7 +393,7 @@ pub trait Decoder {
 
     /// Decodes text data and returns the result as a Vec<u8>.
     ///
-    /// This method is only available when the `std` feature is enabled.
+    /// This method is only available when the `alloc` feature is enabled.
     ///
     /// # Arguments
     ///
@@ -226,18 +404,202 @@ pub trait Decoder {
     ///
     /// * `Ok(Vec<u8>)` - A Vec containing the decoded binary data
     /// * `Err(Error::InvalidInput)` - If the input contains invalid characters
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     fn decode_to_vec<IN: AsRef<[u8]>>(
         encoded: IN,
         ignore: Option<&[u8]>,
     ) -> Result<Vec<u8>, Error> {
         let mut bin = vec![0u8; encoded.as_ref().len()];
+        let bin_len = match Self::decode(&mut bin, encoded, ignore) {
+            Ok(bin) => bin.len(),
+            Err(e) => {
+                clear(&mut bin);
+                return Err(e);
+            }
+        };
+        bin.truncate(bin_len);
+        Ok(bin)
+    }
+
+    /// Decodes text data and appends the result to an existing Vec<u8>.
+    ///
+    /// The input is validated first, so that exactly the decoded length is
+    /// reserved. If `bin` has to grow, its content is moved to a new allocation
+    /// and the old one is cleared before being freed. Spare capacity that held
+    /// data before `bin` was last truncated is not cleared. On error, `bin` is
+    /// truncated back to its original length, and any partially decoded data
+    /// is cleared.
+    /// This method is only available when the `alloc` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Vec to append the decoded data to
+    /// * `encoded` - Text input data to decode
+    /// * `ignore` - Optional set of characters to ignore during decoding (e.g., whitespace)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the decoded data was appended
+    /// * `Err(Error::InvalidInput)` - If the input contains invalid characters
+    #[cfg(feature = "alloc")]
+    fn decode_append<IN: AsRef<[u8]>>(
+        bin: &mut Vec<u8>,
+        encoded: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<(), Error>
+    where
+        Self: DecoderExt,
+    {
+        let encoded = encoded.as_ref();
+        let bin_len = Self::validate(encoded, ignore)?;
+        let previous_len = bin.len();
+        let new_len = previous_len.checked_add(bin_len).ok_or(Error::Overflow)?;
+        if bin.capacity() < new_len {
+            // Letting the Vec reallocate would leave a copy of its content in freed memory.
+            let mut grown = Vec::new();
+            grown
+                .try_reserve_exact(new_len)
+                .map_err(|_| Error::Overflow)?;
+            grown.extend_from_slice(bin);
+            clear(bin);
+            *bin = grown;
+        }
+        bin.resize(new_len, 0);
+        if let Err(e) = Self::decode(&mut bin[previous_len..], encoded, ignore) {
+            clear(&mut bin[previous_len..]);
+            bin.truncate(previous_len);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Decodes text data and returns the result as a Vec<u8> that is wiped when dropped.
+    ///
+    /// Use this for secrets such as private keys, so that they don't linger
+    /// in freed memory. This method is only available when the `alloc` and
+    /// `zeroize` features are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded` - Text input data to decode
+    /// * `ignore` - Optional set of characters to ignore during decoding (e.g., whitespace)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Zeroizing<Vec<u8>>)` - A Vec containing the decoded binary data
+    /// * `Err(Error::InvalidInput)` - If the input contains invalid characters
+    #[cfg(all(feature = "alloc", feature = "zeroize"))]
+    fn decode_to_secret<IN: AsRef<[u8]>>(
+        encoded: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<Zeroizing<Vec<u8>>, Error> {
+        let mut bin = Zeroizing::new(vec![0u8; encoded.as_ref().len()]);
         let bin_len = Self::decode(&mut bin, encoded, ignore)?.len();
         bin.truncate(bin_len);
         Ok(bin)
     }
 }
 
+/// Additional decoding operations, implemented by every decoder of the crate.
+///
+/// These need access to the internals of a decoder, so they are not part of
+/// [`Decoder`], which can be implemented by other crates. This trait is sealed:
+/// it cannot be implemented outside of this crate.
+pub trait DecoderExt: Decoder + sealed::Sealed {
+    /// Validates text data without decoding it.
+    ///
+    /// This runs the same strict checks as [`decode`](Decoder::decode)
+    /// (alphabet, padding, non-canonical trailing bits), in constant time
+    /// relative to the input length, but doesn't require an output buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded` - Text input data to validate
+    /// * `ignore` - Optional set of characters to ignore (e.g., whitespace)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - The length of the decoded data
+    /// * `Err(Error::InvalidInput)` - If the input isn't valid for the given encoding
+    fn validate<IN: AsRef<[u8]>>(encoded: IN, ignore: Option<&[u8]>) -> Result<usize, Error>;
+
+    /// Decodes text data over itself.
+    ///
+    /// Decoded data is always shorter than the encoded data, so the buffer
+    /// holding the encoded input is overwritten with the decoded output,
+    /// without requiring any extra memory. This applies the same strict checks
+    /// as [`decode`](Decoder::decode). If an error is returned, the content
+    /// of the buffer is unspecified.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer containing the encoded data, overwritten with the decoded data
+    /// * `ignore` - Optional set of characters to ignore during decoding (e.g., whitespace)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - A slice of the buffer containing the decoded data
+    /// * `Err(Error::InvalidInput)` - If the input isn't valid for the given encoding
+    fn decode_in_place<'t>(buf: &'t mut [u8], ignore: Option<&[u8]>) -> Result<&'t [u8], Error>;
+
+    /// Decodes text data, reporting a detailed error on failure.
+    ///
+    /// On success, this is equivalent to [`decode`](Decoder::decode). On failure,
+    /// the input is analyzed again to find out what is wrong with it and where.
+    ///
+    /// **That analysis is not constant-time.** Only use this function on public
+    /// inputs, for example to return helpful error messages to users.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin` - Mutable buffer to store the decoded output
+    /// * `encoded` - Text input data to decode
+    /// * `ignore` - Optional set of characters to ignore during decoding (e.g., whitespace)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - A slice of the binary buffer containing the decoded data
+    /// * `Err(Diagnostic)` - The kind of error, and the offset of the offending character
+    fn diagnose<'t, IN: AsRef<[u8]>>(
+        bin: &'t mut [u8],
+        encoded: IN,
+        ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Diagnostic>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    macro_rules! impl_sealed {
+        ($($t:ty),*) => {
+            $(impl Sealed for $t {})*
+        };
+    }
+
+    impl_sealed!(
+        crate::Base64,
+        crate::Base64NoPadding,
+        crate::Base64UrlSafe,
+        crate::Base64UrlSafeNoPadding,
+        crate::Base64Mixed,
+        crate::Base64MixedNoPadding,
+        crate::Base32,
+        crate::Base32NoPadding,
+        crate::Base32Hex,
+        crate::Base32HexNoPadding,
+        crate::Hex
+    );
+}
+
+// Wipes a temporary buffer that may contain secrets.
+#[inline]
+pub(crate) fn clear(buf: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+    #[cfg(not(feature = "zeroize"))]
+    buf.iter_mut().for_each(|x| *x = 0);
+}
+
 /// Constant-time equality check for two byte slices.
 ///
 /// # Arguments
@@ -268,3 +630,450 @@ pub fn verify(x: &[u8], y: &[u8]) -> bool {
     }
     v == 0
 }
+
+/// Checks whether encoded data decodes to the expected binary data.
+///
+/// The input is validated as a whole, then decoded in small chunks, that are
+/// compared with the expected data in constant time, without branching on the
+/// decoded content. The only temporary storage is a small stack buffer, which
+/// is cleared before returning. Characters cannot be ignored.
+///
+/// # Arguments
+///
+/// * `encoded` - Encoded data, as produced by `D`
+/// * `expected` - Binary data the input is expected to decode to
+///
+/// # Returns
+///
+/// * `bool` - `true` if the input is valid for `D` and decodes to `expected`, `false` otherwise
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::{verify_encoded, Base64};
+///
+/// assert!(verify_encoded::<Base64, _>("SGVsbG8=", b"Hello"));
+/// assert!(!verify_encoded::<Base64, _>("SGVsbG8=", b"Hellp"));
+/// assert!(!verify_encoded::<Base64, _>("SGVsbG8", b"Hello"));
+/// ```
+pub fn verify_encoded<D: DecoderExt, IN: AsRef<[u8]>>(encoded: IN, expected: &[u8]) -> bool {
+    let encoded = encoded.as_ref();
+    // Chunks are decoded independently, and each of them could end with padding.
+    let valid = D::validate(encoded, None).is_ok();
+    let mut expected_pos = 0usize;
+    let mut matches = ct::Choice::TRUE;
+    let res = transcode::decode_chunks::<D, _>(encoded, |bin| {
+        let expected_chunk = expected
+            .get(expected_pos..expected_pos + bin.len())
+            .ok_or(Error::InvalidInput)?;
+        matches = matches & ct::ct_eq(bin, expected_chunk);
+        expected_pos += bin.len();
+        Ok(())
+    });
+    valid & res.is_ok() & (expected_pos == expected.len()) & bool::from(matches)
+}
+
+/// Decodes data that can be encoded with either of two codecs.
+///
+/// Both decoders always run over the whole input, and the output is selected
+/// without branching on which one succeeded: the output of `A` is used if the
+/// input is valid for `A`, and the output of `B` otherwise. The output of `B`
+/// goes through a small stack buffer, which is cleared before returning.
+/// Characters cannot be ignored.
+///
+/// Like the decoders themselves, this doesn't hide the length of the decoded
+/// data, nor the position of the first character that is invalid for a codec.
+///
+/// # Arguments
+///
+/// * `bin` - Mutable buffer to store the decoded output
+/// * `encoded` - Input data, encoded with `A` or `B`
+///
+/// # Returns
+///
+/// * `Ok((&[u8], Choice))` - The decoded data, and whether it was decoded with `B`
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+/// * `Err(Error::InvalidInput)` - If the input is valid for neither codec
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::{decode_either, Base64, Hex};
+///
+/// fn example() -> Result<(), ct_codecs::Error> {
+///     let mut bin = [0u8; 16];
+///     let (decoded, is_base64) = decode_either::<Hex, Base64, _>(&mut bin, "48656c6c6f")?;
+///     assert_eq!((decoded, bool::from(is_base64)), (&b"Hello"[..], false));
+///
+///     let (decoded, is_base64) = decode_either::<Hex, Base64, _>(&mut bin, "SGVsbG8=")?;
+///     assert_eq!((decoded, bool::from(is_base64)), (&b"Hello"[..], true));
+///     Ok(())
+/// }
+/// # example().unwrap();
+/// ```
+pub fn decode_either<A: Decoder, B: DecoderExt, IN: AsRef<[u8]>>(
+    bin: &mut [u8],
+    encoded: IN,
+) -> Result<(&[u8], ct::Choice), Error> {
+    use ct::{expose, secret};
+
+    // The outcome of each decoder is only kept as secret masks, so that it
+    // can't be used as a condition until the final result is known.
+    let encoded = encoded.as_ref();
+    let (len_a, ok_a, overflow_a) =
+        result_masks(A::decode(bin, encoded, None).map(|bin| bin.len()));
+    let use_b = !ok_a;
+
+    // Decode with `B` in chunks, and only keep the output if `A` failed.
+    // Chunks are decoded independently, and each of them could end with
+    // padding, so the whole input is validated first. Every chunk is decoded
+    // and copied, even after an error.
+    let (_, valid_b, _) = result_masks(B::validate(encoded, None).map(|_| 0));
+    let mut chunk = [0u8; transcode::CHUNK_DECODED_MAXLEN];
+    let (mut len_b, mut ok_b, mut overflow_b) = (0usize, valid_b, secret(0x00));
+    for encoded_chunk in encoded.chunks(transcode::CHUNK_ENCODED_LEN) {
+        let decoded = B::decode(&mut chunk, encoded_chunk, None).map(|decoded| decoded.len());
+        let (chunk_len, ok, _) = result_masks(decoded);
+        // Output that doesn't fit in `bin` is dropped, and reported as an
+        // overflow if the input is valid.
+        let copy_len = chunk_len.min(bin.len() - len_b);
+        let fits = secret(((copy_len == chunk_len) as u8).wrapping_neg());
+        ct::ct_copy_if(
+            ct::Choice::from_secret(use_b),
+            &mut bin[len_b..len_b + copy_len],
+            &chunk[..copy_len],
+        );
+        len_b += copy_len;
+        overflow_b |= ok_b & ok & !fits;
+        ok_b &= ok & fits;
+    }
+    clear(&mut chunk);
+
+    let bin_len = select_len(use_b, len_b, len_a);
+    if expose(ok_a | ok_b) == 0 {
+        clear(bin);
+        return Err(if expose(overflow_a | overflow_b) != 0 {
+            Error::Overflow
+        } else {
+            Error::InvalidInput
+        });
+    }
+    Ok((&bin[..bin_len], ct::Choice::from_secret(use_b)))
+}
+
+// Splits the result of a decoder into the decoded length (zero on error), a
+// mask set if it succeeded, and a mask set if it failed with an overflow.
+#[inline]
+fn result_masks(res: Result<usize, Error>) -> (usize, ct::Secret, ct::Secret) {
+    let ok = ct::secret((res.is_ok() as u8).wrapping_neg());
+    let overflow = ct::secret(((res == Err(Error::Overflow)) as u8).wrapping_neg());
+    (res.unwrap_or(0), ok, overflow)
+}
+
+// Returns `if_true` if `mask` is set, `if_false` otherwise, without branching.
+#[inline]
+fn select_len(mask: ct::Secret, if_true: usize, if_false: usize) -> usize {
+    let mask = 0usize.wrapping_sub((ct::expose(mask) & 1) as usize);
+    if_false ^ (mask & (if_false ^ if_true))
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_external_decoder() {
+    // Other crates only have to implement `decode`.
+    struct Identity;
+
+    impl Decoder for Identity {
+        fn decode<'t, IN: AsRef<[u8]>>(
+            bin: &'t mut [u8],
+            encoded: IN,
+            _ignore: Option<&[u8]>,
+        ) -> Result<&'t [u8], Error> {
+            let encoded = encoded.as_ref();
+            let bin = bin.get_mut(..encoded.len()).ok_or(Error::Overflow)?;
+            bin.copy_from_slice(encoded);
+            Ok(bin)
+        }
+    }
+
+    assert_eq!(Identity::decode_to_vec("abc", None).unwrap(), b"abc");
+    assert_eq!(Identity::max_decoded_len(3), 3);
+}
+
+#[cfg(all(feature = "std", feature = "zeroize"))]
+#[test]
+fn test_secret() {
+    let secret = Hex::decode_to_secret("01050b0f1383", None).unwrap();
+    assert_eq!(&secret[..], [1, 5, 11, 15, 19, 131]);
+    let encoded = Base64UrlSafeNoPadding::encode_to_secret(&secret[..]).unwrap();
+    assert_eq!(encoded.as_str(), "AQULDxOD");
+    assert_eq!(
+        Hex::decode_to_secret("01050b0f138", None),
+        Err(Error::InvalidInput)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_append() {
+    let mut encoded = String::from("Authorization: Basic ");
+    Base64::encode_append(&mut encoded, b"user:pass").unwrap();
+    assert_eq!(encoded, "Authorization: Basic dXNlcjpwYXNz");
+    let bin: Vec<u8> = (0..1000u32).map(|i| (i * 13) as u8).collect();
+    let mut encoded = String::from("x");
+    Base32NoPadding::encode_append(&mut encoded, &bin).unwrap();
+    assert_eq!(
+        encoded[1..],
+        Base32NoPadding::encode_to_string(&bin).unwrap()
+    );
+    assert_eq!(encoded.capacity(), encoded.len());
+
+    let mut decoded = vec![0xff];
+    Hex::decode_append(&mut decoded, "01 02 03", Some(b" ")).unwrap();
+    assert_eq!(decoded, [0xff, 1, 2, 3]);
+    assert_eq!(
+        Hex::decode_append(&mut decoded, "0405x", None),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        Base64::decode_append(&mut decoded, "AQI", None),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(decoded, [0xff, 1, 2, 3]);
+    Base32NoPadding::decode_append(&mut decoded, &encoded[1..], None).unwrap();
+    assert_eq!(decoded[4..], bin[..]);
+    assert_eq!(decoded.capacity(), decoded.len());
+
+    let mut decoded = Vec::with_capacity(8);
+    decoded.extend_from_slice(&[0xff, 0xfe]);
+    Hex::decode_append(&mut decoded, "010203", None).unwrap();
+    assert_eq!(decoded, [0xff, 0xfe, 1, 2, 3]);
+    assert_eq!(decoded.capacity(), 8);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_encode_append_error() {
+    // Fails on any chunk containing a zero byte.
+    struct Failing;
+
+    impl sealed::Sealed for Failing {}
+
+    impl Encoder for Failing {
+        fn encoded_len(bin_len: usize) -> Result<usize, Error> {
+            Hex::encoded_len(bin_len)
+        }
+
+        fn encode<IN: AsRef<[u8]>>(encoded: &mut [u8], bin: IN) -> Result<&[u8], Error> {
+            let bin = bin.as_ref();
+            if bin.contains(&0) {
+                return Err(Error::Overflow);
+            }
+            Hex::encode(encoded, bin)
+        }
+    }
+
+    // The first chunks are appended before the last one fails.
+    let mut bin = [0xffu8; 1000];
+    bin[999] = 0;
+    let mut encoded = String::from("x");
+    assert_eq!(
+        Failing::encode_append(&mut encoded, bin),
+        Err(Error::Overflow)
+    );
+    assert_eq!(encoded, "x");
+    bin[999] = 1;
+    Failing::encode_append(&mut encoded, bin).unwrap();
+    assert_eq!(encoded[1..], Hex::encode_to_string(bin).unwrap());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_verify_encoded() {
+    fn check<C: Encoder + DecoderExt>() {
+        for len in [0, 1, 2, 5, 89, 90, 91, 200, 301] {
+            let bin: Vec<u8> = (0..len).map(|i| (i * 31 + 7) as u8).collect();
+            let encoded = C::encode_to_string(&bin).unwrap();
+            assert!(verify_encoded::<C, _>(&encoded, &bin));
+            for i in 0..len {
+                let mut other = bin.clone();
+                other[i] ^= 1;
+                assert!(!verify_encoded::<C, _>(&encoded, &other));
+            }
+            assert!(!verify_encoded::<C, _>(
+                &encoded,
+                &[bin.as_slice(), &[0]].concat()
+            ));
+            if len > 0 {
+                assert!(!verify_encoded::<C, _>(&encoded, &bin[..len - 1]));
+                assert!(!verify_encoded::<C, _>(&encoded[1..], &bin));
+            }
+            assert!(!verify_encoded::<C, _>(format!("{}!", encoded), &bin));
+        }
+    }
+    check::<Base64>();
+    check::<Base64UrlSafeNoPadding>();
+    check::<Base32>();
+    check::<Base32HexNoPadding>();
+    check::<Hex>();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_verify_encoded_invalid() {
+    // `verify_encoded` must be false whenever `decode` fails, even when every
+    // chunk of the input decodes to the expected data on its own.
+    fn check<D: DecoderExt>(encoded: &str, chunks: &[u8]) {
+        assert_eq!(D::decode_to_vec(encoded, None), Err(Error::InvalidInput));
+        assert!(!verify_encoded::<D, _>(encoded, chunks));
+    }
+    let hi = [[0xa5u8; 88].as_slice(), b"hi"].concat();
+    let b64 = Base64::encode_to_string([0xa5u8; 88]).unwrap();
+    check::<Base64>(&(b64.clone() + "aGk="), &hi);
+    check::<Base64>(&(b64 + "aGk"), &hi);
+    let hi = [[0xa5u8; 74].as_slice(), b"hi"].concat();
+    let b32 = Base32::encode_to_string([0xa5u8; 74]).unwrap();
+    check::<Base32>(&(b32 + "NBUQ===="), &hi);
+    check::<Base64>("SGVsbG8", b"Hello");
+    check::<Hex>("48656c6c6", b"Hell");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_decode_either() {
+    let mut bin = [0u8; 256];
+    let (decoded, use_b) = decode_either::<Hex, Base64, _>(&mut bin, "deadbeef").unwrap();
+    assert_eq!(
+        (decoded, bool::from(use_b)),
+        (&[0xde, 0xad, 0xbe, 0xef][..], false)
+    );
+    let (decoded, use_b) = decode_either::<Base64, Hex, _>(&mut bin, "deadbeef").unwrap();
+    assert_eq!(decoded, Base64::decode_to_vec("deadbeef", None).unwrap());
+    assert!(!bool::from(use_b));
+    let (decoded, use_b) = decode_either::<Base64, Base64UrlSafe, _>(&mut bin, "-_8=").unwrap();
+    assert_eq!((decoded, bool::from(use_b)), (&[0xfb, 0xff][..], true));
+    let (decoded, _) = decode_either::<Hex, Base64, _>(&mut bin, "").unwrap();
+    assert!(decoded.is_empty());
+
+    // Long inputs are decoded with `B` in several chunks.
+    let data: Vec<u8> = (0..200u8).collect();
+    let b32 = Base32::encode_to_string(&data).unwrap();
+    let (decoded, use_b) = decode_either::<Hex, Base32, _>(&mut bin, &b32).unwrap();
+    assert_eq!((decoded, bool::from(use_b)), (&data[..], true));
+    let hex = Hex::encode_to_string(&data).unwrap();
+    let (decoded, use_b) = decode_either::<Base32, Hex, _>(&mut bin, &hex).unwrap();
+    assert_eq!((decoded, bool::from(use_b)), (&data[..], true));
+
+    assert_eq!(
+        decode_either::<Hex, Base64, _>(&mut bin, "SGVsbG8!").unwrap_err(),
+        Error::InvalidInput
+    );
+    assert!(bin.iter().all(|&x| x == 0));
+    assert_eq!(
+        decode_either::<Hex, Base32, _>(&mut bin[..100], &b32).unwrap_err(),
+        Error::Overflow
+    );
+
+    // Padding at the end of a chunk that isn't the last one.
+    let b64 = Base64::encode_to_string([0xa5u8; 88]).unwrap() + "aGk=";
+    assert_eq!(Base64::decode_to_vec(&b64, None), Err(Error::InvalidInput));
+    assert_eq!(
+        decode_either::<Hex, Base64, _>(&mut bin, &b64).unwrap_err(),
+        Error::InvalidInput
+    );
+    let b32 = Base32::encode_to_string([0xa5u8; 74]).unwrap() + "NBUQ====";
+    assert_eq!(
+        decode_either::<Hex, Base32, _>(&mut bin, &b32).unwrap_err(),
+        Error::InvalidInput
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_decode_either_trace() {
+    use std::cell::RefCell;
+
+    std::thread_local! {
+        static TRACE: RefCell<Vec<(&'static str, usize, usize)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Records every call made to a decoder, with the lengths of its arguments.
+    struct Traced<D>(core::marker::PhantomData<D>);
+
+    impl<D> sealed::Sealed for Traced<D> {}
+
+    impl<D: DecoderExt> Decoder for Traced<D> {
+        fn decode<'t, IN: AsRef<[u8]>>(
+            bin: &'t mut [u8],
+            encoded: IN,
+            ignore: Option<&[u8]>,
+        ) -> Result<&'t [u8], Error> {
+            let encoded = encoded.as_ref();
+            TRACE.with(|t| t.borrow_mut().push(("decode", bin.len(), encoded.len())));
+            D::decode(bin, encoded, ignore)
+        }
+    }
+
+    impl<D: DecoderExt> DecoderExt for Traced<D> {
+        fn validate<IN: AsRef<[u8]>>(encoded: IN, ignore: Option<&[u8]>) -> Result<usize, Error> {
+            let encoded = encoded.as_ref();
+            TRACE.with(|t| t.borrow_mut().push(("validate", 0, encoded.len())));
+            D::validate(encoded, ignore)
+        }
+
+        fn decode_in_place<'t>(
+            _buf: &'t mut [u8],
+            _ignore: Option<&[u8]>,
+        ) -> Result<&'t [u8], Error> {
+            unreachable!()
+        }
+
+        fn diagnose<'t, IN: AsRef<[u8]>>(
+            _bin: &'t mut [u8],
+            _encoded: IN,
+            _ignore: Option<&[u8]>,
+        ) -> Result<&'t [u8], Diagnostic> {
+            unreachable!()
+        }
+    }
+
+    let run = |encoded: &str, bin_len: usize| {
+        TRACE.with(|t| t.borrow_mut().clear());
+        let mut bin = [0u8; 128];
+        let res =
+            decode_either::<Traced<Hex>, Traced<Base64NoPadding>, _>(&mut bin[..bin_len], encoded)
+                .map(|(bin, use_b)| (bin.len(), bool::from(use_b)));
+        (res, TRACE.with(|t| t.borrow().clone()))
+    };
+
+    // Inputs of the same length that are valid for the first codec only, for
+    // the second codec only, for both, and for neither. Whichever codec ends up
+    // being used, the same calls must be made. In tests, the masks selecting
+    // the result are `Tainted`, so branching on them would also panic here.
+    let long_a = format!("{}f", "d".repeat(121));
+    let long_b = format!("S{}", "A".repeat(121));
+    let long_both = "A".repeat(122);
+    let long_neither = format!("{}!", "A".repeat(121));
+    let cases: [(&str, &str, &str, &str); 2] = [
+        ("deadbe", "SGVsbA", "deadbA", "SGVsb!"),
+        (&long_a, &long_b, &long_both, &long_neither),
+    ];
+    for &(a, b, both, neither) in &cases {
+        let (res_a, trace) = run(a, 128);
+        assert_eq!(res_a, Ok((a.len() / 2, false)));
+        let (res_b, trace_b) = run(b, 128);
+        assert_eq!(res_b, Ok((b.len() * 3 / 4, true)));
+        assert_eq!(trace_b, trace);
+        let (res_both, trace_both) = run(both, 128);
+        assert_eq!(res_both, Ok((both.len() / 2, false)));
+        assert_eq!(trace_both, trace);
+        let (res_neither, trace_neither) = run(neither, 128);
+        assert_eq!(res_neither, Err(Error::InvalidInput));
+        assert_eq!(trace_neither, trace);
+        // Failures of both codecs, including overflows, take the same path.
+        let (res_overflow, trace_overflow) = run(b, 1);
+        assert_eq!(res_overflow, Err(Error::Overflow));
+        let (_, trace_small) = run(a, 1);
+        assert_eq!(trace_overflow, trace_small);
+    }
+}
@@ -0,0 +1,280 @@
+// Maximum length of the data encoded as `encoded`, ignoring padding, with
+// `bits` bits per character. The decoders reject any other length.
+#[doc(hidden)]
+pub const fn decoded_len(encoded: &[u8], bits: usize) -> usize {
+    let mut len = encoded.len();
+    while len > 0 && encoded[len - 1] == b'=' {
+        len -= 1;
+    }
+    len * bits / 8
+}
+
+// Adds a `decode_array` function to a codec. `$decode` is the strict decoder
+// usable in constant expressions of its family, called with the encoded data
+// followed by `$args`.
+macro_rules! impl_decode_array {
+    ($codec:ident, $macro:literal, $decode:path $(, $args:expr)*) => {
+        impl $codec {
+            /// Decodes data into a fixed-size array, in a constant expression if needed.
+            ///
+            /// The same strict checks as [`Decoder::decode`](crate::Decoder::decode)
+            /// apply, characters cannot be ignored, and the decoded data must be
+            /// exactly `N` bytes long.
+            #[doc = concat!("This is what the [`", $macro, "`](crate::", $macro, ") macro uses.")]
+            ///
+            /// # Arguments
+            ///
+            /// * `encoded` - Encoded input data to decode
+            ///
+            /// # Returns
+            ///
+            /// * `Ok([u8; N])` - The decoded data
+            /// * `Err(Error::Overflow)` - If the decoded data is longer than `N` bytes
+            /// * `Err(Error::InvalidInput)` - If the input is invalid or decodes to less than `N` bytes
+            pub const fn decode_array<const N: usize>(encoded: &[u8]) -> Result<[u8; N], Error> {
+                $decode(encoded $(, $args)*)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_decode_array;
+
+/// Decodes a hexadecimal string literal into a `[u8; N]` array at compile time.
+///
+/// Both lowercase and uppercase characters are accepted. Invalid input is a
+/// compile error.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::hex;
+///
+/// const KEY: [u8; 4] = hex!("deadbeef");
+/// assert_eq!(KEY, [0xde, 0xad, 0xbe, 0xef]);
+/// ```
+///
+/// ```compile_fail
+/// const KEY: [u8; 1] = ct_codecs::hex!("dex");
+/// ```
+#[macro_export]
+macro_rules! hex {
+    ($hex:expr) => {{
+        const ENCODED: &[u8] = $hex.as_bytes();
+        const BIN: [u8; $crate::__private::decoded_len(ENCODED, 4)] =
+            match $crate::Hex::decode_array(ENCODED) {
+                ::core::result::Result::Ok(bin) => bin,
+                ::core::result::Result::Err(_) => ::core::panic!("invalid hexadecimal literal"),
+            };
+        BIN
+    }};
+}
+
+/// Decodes a Base64 string literal into a `[u8; N]` array at compile time.
+///
+/// The standard alphabet with padding is expected by default. Another Base64
+/// codec can be given as the first argument. The input must be canonical:
+/// invalid characters, missing or extra padding and non-zero trailing bits are
+/// compile errors.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::base64;
+///
+/// const KEY: [u8; 7] = base64!("AQULDxODeg==");
+/// assert_eq!(KEY, [1, 5, 11, 15, 19, 131, 122]);
+///
+/// const TOKEN: [u8; 4] = base64!(Base64UrlSafeNoPadding, "----3g");
+/// assert_eq!(TOKEN, [251, 239, 190, 222]);
+/// ```
+///
+/// ```compile_fail
+/// // Non-canonical: the trailing bits are not zero
+/// const KEY: [u8; 7] = ct_codecs::base64!("AQULDxODeh==");
+/// ```
+#[macro_export]
+macro_rules! base64 {
+    ($b64:expr) => {
+        $crate::base64!(Base64, $b64)
+    };
+    ($codec:ident, $b64:expr) => {{
+        const ENCODED: &[u8] = $b64.as_bytes();
+        const BIN: [u8; $crate::__private::decoded_len(ENCODED, 6)] =
+            match $crate::$codec::decode_array(ENCODED) {
+                ::core::result::Result::Ok(bin) => bin,
+                ::core::result::Result::Err(_) => ::core::panic!("invalid Base64 literal"),
+            };
+        BIN
+    }};
+}
+
+/// Decodes a Base32 string literal into a `[u8; N]` array at compile time.
+///
+/// The standard alphabet with padding is expected by default. Another Base32
+/// codec can be given as the first argument. The input must be canonical:
+/// invalid characters, missing or extra padding and non-zero trailing bits are
+/// compile errors.
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::base32;
+///
+/// const SECRET: [u8; 6] = base32!("MZXW6YTBOI======");
+/// assert_eq!(&SECRET, b"foobar");
+///
+/// const ID: [u8; 6] = base32!(Base32HexNoPadding, "CPNMUOJ1E8");
+/// assert_eq!(&ID, b"foobar");
+/// ```
+///
+/// ```compile_fail
+/// // Lowercase characters are not part of the alphabet
+/// const SECRET: [u8; 6] = ct_codecs::base32!("mzxw6ytboi======");
+/// ```
+#[macro_export]
+macro_rules! base32 {
+    ($b32:expr) => {
+        $crate::base32!(Base32, $b32)
+    };
+    ($codec:ident, $b32:expr) => {{
+        const ENCODED: &[u8] = $b32.as_bytes();
+        const BIN: [u8; $crate::__private::decoded_len(ENCODED, 5)] =
+            match $crate::$codec::decode_array(ENCODED) {
+                ::core::result::Result::Ok(bin) => bin,
+                ::core::result::Result::Err(_) => ::core::panic!("invalid Base32 literal"),
+            };
+        BIN
+    }};
+}
+
+#[test]
+fn test_literal_macros() {
+    const EMPTY: [u8; 0] = hex!("");
+    assert!(EMPTY.is_empty());
+    assert_eq!(hex!("00fF10"), [0x00, 0xff, 0x10]);
+    assert_eq!(base64!("AQULDxODeg=="), [1, 5, 11, 15, 19, 131, 122]);
+    assert_eq!(base64!(Base64NoPadding, "+/+/"), [251, 255, 191]);
+    assert_eq!(base64!(Base64UrlSafe, "-_8="), [251, 255]);
+    assert_eq!(base32!("MZXW6YQ="), *b"foob");
+    assert_eq!(base32!(Base32NoPadding, "MZXW6YQ"), *b"foob");
+    assert_eq!(base32!(Base32Hex, "CPNMUOG="), *b"foob");
+}
+
+#[test]
+fn test_decode_array() {
+    use crate::*;
+
+    const KEY: Result<[u8; 3], Error> = Base64::decode_array(b"AQUL");
+    assert_eq!(KEY, Ok([1, 5, 11]));
+    assert_eq!(Base64::decode_array::<2>(b"AQUL"), Err(Error::Overflow));
+    assert_eq!(Base64::decode_array::<4>(b"AQUL"), Err(Error::InvalidInput));
+    assert_eq!(Base64::decode_array::<1>(b"AQ"), Err(Error::InvalidInput));
+    assert_eq!(Base64NoPadding::decode_array::<1>(b"AQ"), Ok([1]));
+    assert_eq!(
+        Base64NoPadding::decode_array::<1>(b"AQ=="),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(Base64::decode_array::<1>(b"AR=="), Err(Error::InvalidInput));
+    assert_eq!(
+        Base64UrlSafe::decode_array::<1>(b"+w=="),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(Base32::decode_array::<1>(b"ME======"), Ok([b'a']));
+    assert_eq!(
+        Base32::decode_array::<1>(b"ME====="),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(Base32HexNoPadding::decode_array::<1>(b"C4"), Ok([b'a']));
+    assert_eq!(Hex::decode_array::<2>(b"0a0B"), Ok([0x0a, 0x0b]));
+    assert_eq!(Hex::decode_array::<2>(b"0a0"), Err(Error::InvalidInput));
+    assert_eq!(Hex::decode_array::<1>(b"0a0b"), Err(Error::Overflow));
+    assert_eq!(Hex::decode_array::<2>(b"0a 0b"), Err(Error::InvalidInput));
+
+    let bin: [u8; 40] = core::array::from_fn(|i| (i * 37) as u8);
+    for len in 0..40 {
+        let mut b64 = [0u8; 64];
+        let b64 = Base64UrlSafeNoPadding::encode(&mut b64, &bin[..len]).unwrap();
+        let mut b32 = [0u8; 64];
+        let b32 = Base32::encode(&mut b32, &bin[..len]).unwrap();
+        let expected: Result<&[u8], _> = if len == 20 { Ok(&bin[..20]) } else { Err(()) };
+        let decoded = Base64UrlSafeNoPadding::decode_array::<20>(b64);
+        assert_eq!(decoded.as_ref().map(|x| &x[..]).map_err(|_| ()), expected);
+        let decoded = Base32::decode_array::<20>(b32);
+        assert_eq!(decoded.as_ref().map(|x| &x[..]).map_err(|_| ()), expected);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_decode_array_matches_decode() {
+    use crate::*;
+
+    // Characters that are valid, invalid or padding for some of the codecs.
+    const CHARS: &[u8] = b"AaZz09+/-_=fF \nG!";
+
+    // Expected result of `decode_array::<N>`, from the regular decoder.
+    fn expected<D: Decoder, const N: usize>(encoded: &[u8]) -> Result<[u8; N], Error> {
+        let mut bin = [0u8; N];
+        let bin_len = D::decode(&mut bin, encoded, None)?.len();
+        if bin_len != N {
+            return Err(Error::InvalidInput);
+        }
+        Ok(bin)
+    }
+
+    // Valid encodings of every length up to `N + 1` bytes, with every
+    // character replaced, removed or duplicated, and every character appended.
+    fn inputs<E: Encoder, const N: usize>() -> Vec<Vec<u8>> {
+        let mut inputs = Vec::new();
+        for len in 0..=N + 1 {
+            let bin: Vec<u8> = (0..len).map(|i| (i * 151 + 29) as u8).collect();
+            let encoded = E::encode_to_string(&bin).unwrap().into_bytes();
+            for pos in 0..=encoded.len() {
+                for &c in CHARS {
+                    let mut input = encoded.clone();
+                    input.insert(pos, c);
+                    inputs.push(input);
+                    if pos < encoded.len() {
+                        let mut input = encoded.clone();
+                        input[pos] = c;
+                        inputs.push(input);
+                    }
+                }
+                if pos < encoded.len() {
+                    let mut input = encoded.clone();
+                    input.remove(pos);
+                    inputs.push(input);
+                }
+            }
+            inputs.push(encoded);
+        }
+        inputs
+    }
+
+    macro_rules! check {
+        ($codec:ident, $($n:literal),*) => {
+            $(
+                for input in inputs::<$codec, $n>() {
+                    assert_eq!(
+                        $codec::decode_array::<$n>(&input),
+                        expected::<$codec, $n>(&input),
+                        "{} {:?}",
+                        stringify!($codec),
+                        String::from_utf8_lossy(&input)
+                    );
+                }
+            )*
+        };
+    }
+
+    check!(Base64, 0, 1, 2, 3, 4, 5, 6);
+    check!(Base64NoPadding, 0, 1, 2, 3, 4, 5, 6);
+    check!(Base64UrlSafe, 0, 1, 2, 3, 4, 5, 6);
+    check!(Base64UrlSafeNoPadding, 0, 1, 2, 3, 4, 5, 6);
+    check!(Base32, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+    check!(Base32NoPadding, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+    check!(Base32Hex, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+    check!(Base32HexNoPadding, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+    check!(Hex, 0, 1, 2, 3);
+}
@@ -0,0 +1,344 @@
+//! Length-hiding padding, before encoding.
+//!
+//! Encoded secrets such as passwords and tokens reveal their length through
+//! the length of the encoded string. Padding the data to a multiple of a
+//! block size before encoding makes all the secrets of the same bucket look
+//! identical.
+//!
+//! The padding is the ISO/IEC 7816-4 scheme, also used by libsodium's
+//! `sodium_pad()`: a `0x80` byte followed by zeros up to the next multiple of
+//! the block size. At least one byte is always added, so that the padding can
+//! always be removed unambiguously. Only the last block is inspected or
+//! modified, in constant time relative to the block size.
+//!
+//! # Examples
+//!
+//! ```
+//! use ct_codecs::padding;
+//! use ct_codecs::Base64UrlSafeNoPadding;
+//!
+//! fn example() -> Result<(), ct_codecs::Error> {
+//!     let mut short = [0u8; 64];
+//!     let short = padding::encode_padded::<Base64UrlSafeNoPadding, _>(&mut short, "secret", 32)?;
+//!     let mut long = [0u8; 64];
+//!     let long = padding::encode_padded::<Base64UrlSafeNoPadding, _>(&mut long, "longer secret", 32)?;
+//!     assert_eq!(short.len(), long.len());
+//!
+//!     let mut bin = [0u8; 32];
+//!     let secret = padding::decode_padded::<Base64UrlSafeNoPadding, _>(&mut bin, short, 32)?;
+//!     assert_eq!(secret, b"secret");
+//!     Ok(())
+//! }
+//! # example().unwrap();
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::ct::*;
+use crate::error::*;
+use crate::{clear, Decoder, EncoderExt};
+
+const MARKER: u8 = 0x80;
+
+// Returns `usize::MAX` if `x < y`, 0 otherwise. Both values are slice
+// lengths or positions, so they are below `isize::MAX`.
+#[inline]
+fn lt_mask(x: usize, y: usize) -> usize {
+    0usize.wrapping_sub(x.wrapping_sub(y) >> (usize::BITS - 1))
+}
+
+/// Calculates the length of padded data.
+///
+/// # Arguments
+///
+/// * `unpadded_len` - The length of the data before padding
+/// * `block_size` - The block size to pad to
+///
+/// # Returns
+///
+/// * `Ok(usize)` - The next multiple of `block_size` strictly above `unpadded_len`
+/// * `Err(Error::Overflow)` - If the calculation would overflow
+/// * `Err(Error::InvalidInput)` - If `block_size` is zero
+pub fn padded_len(unpadded_len: usize, block_size: usize) -> Result<usize, Error> {
+    if block_size == 0 {
+        return Err(Error::InvalidInput);
+    }
+    (unpadded_len / block_size + 1)
+        .checked_mul(block_size)
+        .filter(|&len| len <= isize::MAX as usize)
+        .ok_or(Error::Overflow)
+}
+
+/// Pads data over itself.
+///
+/// The first `unpadded_len` bytes of `buf` are the data to pad. Only the last
+/// block is written, and every byte of it is processed the same way.
+///
+/// # Arguments
+///
+/// * `buf` - Buffer starting with the data, at least [`padded_len`] bytes long
+/// * `unpadded_len` - The length of the data
+/// * `block_size` - The block size to pad to
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the buffer containing the padded data
+/// * `Err(Error::Overflow)` - If the buffer is too small
+/// * `Err(Error::InvalidInput)` - If `block_size` is zero
+pub fn pad(buf: &mut [u8], unpadded_len: usize, block_size: usize) -> Result<&[u8], Error> {
+    let padded_len = padded_len(unpadded_len, block_size)?;
+    if buf.len() < padded_len {
+        return Err(Error::Overflow);
+    }
+    let tail_pos = padded_len - block_size;
+    for (i, c) in buf[tail_pos..padded_len].iter_mut().enumerate() {
+        let pos = tail_pos + i;
+        let is_data = lt_mask(pos, unpadded_len) as u8;
+        let is_marker = !(lt_mask(pos, unpadded_len) | lt_mask(unpadded_len, pos)) as u8;
+        *c = (*c & is_data) | (MARKER & is_marker);
+    }
+    Ok(&buf[..padded_len])
+}
+
+/// Removes the padding from padded data.
+///
+/// Only the last block is inspected, in constant time relative to the block size.
+///
+/// # Arguments
+///
+/// * `padded` - Padded data
+/// * `block_size` - The block size the data was padded to
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the padded data, without the padding
+/// * `Err(Error::InvalidInput)` - If the data is not correctly padded, or if `block_size` is zero
+pub fn unpad(padded: &[u8], block_size: usize) -> Result<&[u8], Error> {
+    if block_size == 0 {
+        return Err(Error::InvalidInput);
+    }
+    let blocks = padded.len() / block_size;
+    if blocks == 0 || blocks * block_size != padded.len() {
+        return Err(Error::InvalidInput);
+    }
+    let tail_pos = padded.len() - block_size;
+    let mut unpadded_len = 0usize;
+    let mut valid = Secret::new(0);
+    // Whether a non-zero byte has been seen, going backwards.
+    let mut seen = Secret::new(0);
+    for (i, &c) in padded[tail_pos..].iter().enumerate().rev() {
+        let c = Secret::new(c);
+        let is_marker = !seen & _eq(c, MARKER);
+        unpadded_len |= 0usize.wrapping_sub((is_marker.expose() & 1) as usize) & (tail_pos + i);
+        valid |= is_marker;
+        seen |= !_eq(c, 0);
+    }
+    if valid.expose() == 0 {
+        return Err(Error::InvalidInput);
+    }
+    Ok(&padded[..unpadded_len])
+}
+
+/// Pads binary data to a multiple of a block size, and encodes it.
+///
+/// The padded data is built in the output buffer, which is then encoded
+/// over itself, so no other buffer is needed.
+///
+/// # Arguments
+///
+/// * `encoded` - Mutable buffer to store the encoded output
+/// * `bin` - Binary input data to pad and encode
+/// * `block_size` - The block size to pad to
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the encoded buffer containing the encoded data
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+/// * `Err(Error::InvalidInput)` - If `block_size` is zero
+pub fn encode_padded<E: EncoderExt, IN: AsRef<[u8]>>(
+    encoded: &mut [u8],
+    bin: IN,
+    block_size: usize,
+) -> Result<&[u8], Error> {
+    let bin = bin.as_ref();
+    let padded_len = padded_len(bin.len(), block_size)?;
+    if encoded.len() < padded_len || encoded.len() < E::encoded_len(padded_len)? {
+        return Err(Error::Overflow);
+    }
+    encoded[..bin.len()].copy_from_slice(bin);
+    pad(encoded, bin.len(), block_size)?;
+    E::encode_in_place(encoded, padded_len)
+}
+
+/// Decodes padded data, and removes the padding.
+///
+/// # Arguments
+///
+/// * `bin` - Mutable buffer to store the decoded output, including the padding
+/// * `encoded` - Encoded, padded data
+/// * `block_size` - The block size the data was padded to
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the binary buffer containing the decoded data, without the padding
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+/// * `Err(Error::InvalidInput)` - If the input is invalid or not correctly padded
+pub fn decode_padded<D: Decoder, IN: AsRef<[u8]>>(
+    bin: &mut [u8],
+    encoded: IN,
+    block_size: usize,
+) -> Result<&[u8], Error> {
+    let padded_len = D::decode(bin, encoded, None)?.len();
+    match unpad(&bin[..padded_len], block_size) {
+        Ok(unpadded) => {
+            let unpadded_len = unpadded.len();
+            Ok(&bin[..unpadded_len])
+        }
+        Err(e) => {
+            clear(&mut bin[..padded_len]);
+            Err(e)
+        }
+    }
+}
+
+/// Pads binary data to a multiple of a block size, and encodes it into a `String`.
+///
+/// This function is only available when the `alloc` feature is enabled.
+///
+/// # Arguments
+///
+/// * `bin` - Binary input data to pad and encode
+/// * `block_size` - The block size to pad to
+///
+/// # Returns
+///
+/// * `Ok(String)` - A String containing the encoded data
+/// * `Err(Error::Overflow)` - If the calculation of the encoded length overflows
+/// * `Err(Error::InvalidInput)` - If `block_size` is zero
+#[cfg(feature = "alloc")]
+pub fn encode_padded_to_string<E: EncoderExt, IN: AsRef<[u8]>>(
+    bin: IN,
+    block_size: usize,
+) -> Result<String, Error> {
+    let bin = bin.as_ref();
+    let padded_len = padded_len(bin.len(), block_size)?;
+    let mut encoded = vec![0u8; core::cmp::max(padded_len, E::encoded_len(padded_len)?)];
+    let encoded_len = match encode_padded::<E, _>(&mut encoded, bin, block_size) {
+        Ok(encoded) => encoded.len(),
+        Err(e) => {
+            clear(&mut encoded);
+            return Err(e);
+        }
+    };
+    encoded.truncate(encoded_len);
+    Ok(String::from_utf8(encoded).unwrap())
+}
+
+/// Decodes padded data, and removes the padding, returning a `Vec<u8>`.
+///
+/// This function is only available when the `alloc` feature is enabled.
+///
+/// # Arguments
+///
+/// * `encoded` - Encoded, padded data
+/// * `block_size` - The block size the data was padded to
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` - A Vec containing the decoded data, without the padding
+/// * `Err(Error::InvalidInput)` - If the input is invalid or not correctly padded
+#[cfg(feature = "alloc")]
+pub fn decode_padded_to_vec<D: Decoder, IN: AsRef<[u8]>>(
+    encoded: IN,
+    block_size: usize,
+) -> Result<Vec<u8>, Error> {
+    let encoded = encoded.as_ref();
+    let mut bin = vec![0u8; encoded.len()];
+    let bin_len = match decode_padded::<D, _>(&mut bin, encoded, block_size) {
+        Ok(bin) => bin.len(),
+        Err(e) => {
+            clear(&mut bin);
+            return Err(e);
+        }
+    };
+    bin.truncate(bin_len);
+    Ok(bin)
+}
+
+#[test]
+fn test_pad() {
+    for block_size in 1..=17 {
+        for len in 0..=40 {
+            let mut buf = [0xaau8; 64];
+            let padded = pad(&mut buf, len, block_size).unwrap();
+            assert_eq!(padded.len(), padded_len(len, block_size).unwrap());
+            assert_eq!(padded.len() % block_size, 0);
+            assert!(padded.len() > len && padded.len() <= len + block_size);
+            assert!(padded[..len].iter().all(|&c| c == 0xaa));
+            assert_eq!(padded[len], MARKER);
+            assert!(padded[len + 1..].iter().all(|&c| c == 0));
+            assert_eq!(unpad(padded, block_size).unwrap().len(), len);
+        }
+    }
+    assert_eq!(padded_len(5, 0), Err(Error::InvalidInput));
+    assert_eq!(padded_len(usize::MAX, 16), Err(Error::Overflow));
+    assert_eq!(pad(&mut [0u8; 15], 15, 16), Err(Error::Overflow));
+}
+
+#[test]
+fn test_unpad_invalid() {
+    assert_eq!(unpad(&[], 4), Err(Error::InvalidInput));
+    assert_eq!(unpad(&[0x80, 0, 0], 4), Err(Error::InvalidInput));
+    assert_eq!(unpad(&[1, 2, 3, 4], 4), Err(Error::InvalidInput));
+    assert_eq!(unpad(&[0, 0, 0, 0], 4), Err(Error::InvalidInput));
+    assert_eq!(unpad(&[0x80, 0, 0, 1], 4), Err(Error::InvalidInput));
+    assert_eq!(unpad(&[0x80, 0, 0, 0], 0), Err(Error::InvalidInput));
+    // The marker must be in the last block.
+    assert_eq!(
+        unpad(&[0x80, 0, 0, 0, 0, 0, 0, 0], 4),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(unpad(&[0x80, 0x80, 0, 0], 4), Ok(&[0x80][..]));
+    assert_eq!(unpad(&[0x80, 0, 0x80, 0], 4), Ok(&[0x80, 0][..]));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_encode_padded() {
+    use crate::*;
+
+    fn check<C: EncoderExt + Decoder>() {
+        let block_size = 16;
+        let mut lengths = std::collections::BTreeSet::new();
+        for len in 0..block_size {
+            let secret: Vec<u8> = (0..len).map(|i| i as u8 + 1).collect();
+            let encoded = encode_padded_to_string::<C, _>(&secret, block_size).unwrap();
+            lengths.insert(encoded.len());
+            assert_eq!(
+                decode_padded_to_vec::<C, _>(&encoded, block_size).unwrap(),
+                secret
+            );
+            let mut buf = [0u8; 64];
+            let encoded2 = encode_padded::<C, _>(&mut buf, &secret, block_size).unwrap();
+            assert_eq!(encoded2, encoded.as_bytes());
+        }
+        assert_eq!(lengths.len(), 1);
+        let unpadded = C::encode_to_string(b"0123456789abcdef").unwrap();
+        assert_eq!(
+            decode_padded_to_vec::<C, _>(&unpadded, block_size),
+            Err(Error::InvalidInput)
+        );
+    }
+    check::<Base64>();
+    check::<Base64UrlSafeNoPadding>();
+    check::<Base32>();
+    check::<Base32HexNoPadding>();
+    check::<Hex>();
+
+    let mut small = [0u8; 20];
+    assert_eq!(
+        encode_padded::<Hex, _>(&mut small, "secret", 16),
+        Err(Error::Overflow)
+    );
+}
@@ -0,0 +1,277 @@
+//! Serde helpers for binary data stored as encoded strings.
+//!
+//! Each module of this one can be used with `#[serde(with = "...")]` on fields
+//! of type `[u8; N]` and, when the `alloc` feature is enabled, `Vec<u8>` and
+//! `Box<[u8]>`.
+//!
+//! Human-readable formats such as JSON get an encoded string. Binary formats
+//! get the raw bytes instead, as encoding would only make them larger.
+//!
+//! Fixed-size arrays are decoded directly into their final location, and the
+//! decoded length must match exactly.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Jwk {
+//!     #[serde(with = "ct_codecs::serde::base64url_nopad")]
+//!     x: [u8; 32],
+//!     #[serde(with = "ct_codecs::serde::hex")]
+//!     kid: Vec<u8>,
+//! }
+//!
+//! let jwk = Jwk { x: [0xfb; 32], kid: vec![1, 2, 3] };
+//! let json = serde_json::to_string(&jwk).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"x":"-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_s","kid":"010203"}"#
+//! );
+//! let jwk: Jwk = serde_json::from_str(&json).unwrap();
+//! assert_eq!(jwk.x, [0xfb; 32]);
+//! ```
+
+use core::convert::TryInto;
+use core::fmt;
+use core::marker::PhantomData;
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+
+use ::serde::de::{self, Deserializer, Visitor};
+use ::serde::ser::{self, Serializer};
+
+use crate::error::*;
+use crate::{clear, Decoder, Encoder};
+
+// Encoded data up to that length is serialized from a stack buffer.
+const STACK_ENCODED_MAXLEN: usize = 256;
+
+/// Types that binary data can be deserialized into.
+pub trait FromBytes: Sized {
+    /// Decodes `encoded` with `D` into a new value.
+    fn decode_from<D: Decoder>(encoded: &[u8]) -> Result<Self, Error>;
+
+    /// Copies raw bytes into a new value.
+    fn from_bytes(bin: &[u8]) -> Result<Self, Error>;
+}
+
+impl<const N: usize> FromBytes for [u8; N] {
+    fn decode_from<D: Decoder>(encoded: &[u8]) -> Result<Self, Error> {
+        let mut bin = [0u8; N];
+        let err = match D::decode(&mut bin, encoded, None) {
+            Ok(decoded) if decoded.len() == N => return Ok(bin),
+            Ok(_) => Error::InvalidInput,
+            Err(e) => e,
+        };
+        // A shorter or invalid input may have been partially decoded.
+        clear(&mut bin);
+        Err(err)
+    }
+
+    fn from_bytes(bin: &[u8]) -> Result<Self, Error> {
+        bin.try_into().map_err(|_| Error::InvalidInput)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromBytes for Vec<u8> {
+    fn decode_from<D: Decoder>(encoded: &[u8]) -> Result<Self, Error> {
+        D::decode_to_vec(encoded, None)
+    }
+
+    fn from_bytes(bin: &[u8]) -> Result<Self, Error> {
+        Ok(bin.to_vec())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromBytes for Box<[u8]> {
+    fn decode_from<D: Decoder>(encoded: &[u8]) -> Result<Self, Error> {
+        Ok(D::decode_to_vec(encoded, None)?.into_boxed_slice())
+    }
+
+    fn from_bytes(bin: &[u8]) -> Result<Self, Error> {
+        Ok(bin.into())
+    }
+}
+
+fn serialize_with<E: Encoder, S: Serializer>(bin: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        return serializer.serialize_bytes(bin);
+    }
+    let encoded_len = E::encoded_len(bin.len()).map_err(ser::Error::custom)?;
+    if encoded_len <= STACK_ENCODED_MAXLEN {
+        let mut encoded = [0u8; STACK_ENCODED_MAXLEN];
+        let res = match E::encode_to_str(&mut encoded, bin) {
+            Ok(encoded) => serializer.serialize_str(encoded),
+            Err(e) => Err(ser::Error::custom(e)),
+        };
+        clear(&mut encoded);
+        return res;
+    }
+    #[cfg(feature = "alloc")]
+    {
+        let mut encoded = E::encode_to_string(bin)
+            .map_err(ser::Error::custom)?
+            .into_bytes();
+        let res = serializer.serialize_str(core::str::from_utf8(&encoded).unwrap());
+        clear(&mut encoded);
+        res
+    }
+    #[cfg(not(feature = "alloc"))]
+    Err(ser::Error::custom(Error::Overflow))
+}
+
+struct EncodedVisitor<D, T>(PhantomData<(D, T)>);
+
+impl<'de, D: Decoder, T: FromBytes> Visitor<'de> for EncodedVisitor<D, T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an encoded string or a byte array")
+    }
+
+    fn visit_str<ERR: de::Error>(self, v: &str) -> Result<T, ERR> {
+        T::decode_from::<D>(v.as_bytes()).map_err(ERR::custom)
+    }
+
+    fn visit_bytes<ERR: de::Error>(self, v: &[u8]) -> Result<T, ERR> {
+        T::from_bytes(v).map_err(ERR::custom)
+    }
+}
+
+fn deserialize_with<'de, D: Decoder, T: FromBytes, DE: Deserializer<'de>>(
+    deserializer: DE,
+) -> Result<T, DE::Error> {
+    let visitor = EncodedVisitor::<D, T>(PhantomData);
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(visitor)
+    } else {
+        deserializer.deserialize_bytes(visitor)
+    }
+}
+
+macro_rules! codec_module {
+    ($name:ident, $codec:ident, $doc:expr) => {
+        #[doc = $doc]
+        pub mod $name {
+            use super::*;
+            use crate::$codec;
+
+            /// Serializes binary data, encoded for human-readable formats.
+            pub fn serialize<T, S>(bin: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                T: AsRef<[u8]> + ?Sized,
+                S: Serializer,
+            {
+                serialize_with::<$codec, S>(bin.as_ref(), serializer)
+            }
+
+            /// Deserializes binary data, decoded for human-readable formats.
+            pub fn deserialize<'de, T, DE>(deserializer: DE) -> Result<T, DE::Error>
+            where
+                T: FromBytes,
+                DE: Deserializer<'de>,
+            {
+                deserialize_with::<$codec, T, DE>(deserializer)
+            }
+        }
+    };
+}
+
+codec_module!(base64, Base64, "Standard Base64, with padding.");
+codec_module!(
+    base64_nopad,
+    Base64NoPadding,
+    "Standard Base64, without padding."
+);
+codec_module!(base64url, Base64UrlSafe, "URL-safe Base64, with padding.");
+codec_module!(
+    base64url_nopad,
+    Base64UrlSafeNoPadding,
+    "URL-safe Base64, without padding."
+);
+codec_module!(base32, Base32, "Standard Base32, with padding.");
+codec_module!(
+    base32_nopad,
+    Base32NoPadding,
+    "Standard Base32, without padding."
+);
+codec_module!(
+    base32hex,
+    Base32Hex,
+    "Base32 with the extended hex alphabet, with padding."
+);
+codec_module!(
+    base32hex_nopad,
+    Base32HexNoPadding,
+    "Base32 with the extended hex alphabet, without padding."
+);
+codec_module!(hex, Hex, "Lowercase hexadecimal.");
+
+#[cfg(feature = "std")]
+#[test]
+fn test_serde_json() {
+    use ::serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Keys {
+        #[serde(with = "base64")]
+        vec: Vec<u8>,
+        #[serde(with = "base64url_nopad")]
+        array: [u8; 4],
+        #[serde(with = "hex")]
+        boxed: Box<[u8]>,
+        #[serde(with = "base32")]
+        large: Vec<u8>,
+    }
+
+    let keys = Keys {
+        vec: vec![1, 5, 11, 15, 19, 131, 122],
+        array: [251, 239, 190, 222],
+        boxed: vec![0xde, 0xad].into_boxed_slice(),
+        large: vec![0x42; 1000],
+    };
+    let json = serde_json::to_string(&keys).unwrap();
+    assert!(
+        json.starts_with(r#"{"vec":"AQULDxODeg==","array":"----3g","boxed":"dead","large":"IJBE"#)
+    );
+    assert_eq!(serde_json::from_str::<Keys>(&json).unwrap(), keys);
+
+    let json = json.replace("----3g", "----3g--");
+    assert!(serde_json::from_str::<Keys>(&json).is_err());
+    let json = json.replace("----3g--", "----3h");
+    assert!(serde_json::from_str::<Keys>(&json).is_err());
+}
+
+#[test]
+fn test_serde_raw_bytes() {
+    use ::serde::de::value::{BytesDeserializer, Error as ValueError};
+
+    let array: [u8; 3] = base64::deserialize(BytesDeserializer::<ValueError>::new(b"abc")).unwrap();
+    assert_eq!(&array, b"abc");
+    let res: Result<[u8; 4], _> = hex::deserialize(BytesDeserializer::<ValueError>::new(b"abc"));
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_serde_array_length() {
+    use crate::Hex;
+
+    assert_eq!(<[u8; 2]>::decode_from::<Hex>(b"0102"), Ok([1, 2]));
+    assert_eq!(
+        <[u8; 2]>::decode_from::<Hex>(b"01"),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        <[u8; 2]>::decode_from::<Hex>(b"010203"),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        <[u8; 2]>::decode_from::<Hex>(b"01zz"),
+        Err(Error::InvalidInput)
+    );
+}
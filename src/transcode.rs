@@ -0,0 +1,203 @@
+use core::fmt;
+
+use crate::error::*;
+use crate::{clear, Decoder, DecoderExt, EncoderExt};
+
+// Number of encoded characters decoded at once. This is a multiple of the
+// block size of every decoder of the crate (2, 4 and 8 characters), so that
+// chunks never leave bits in the accumulator, and the resulting 60, 75 or 90
+// bytes are multiples of the 3 and 5 byte groups of the encoders, so that
+// intermediate chunks are never padded.
+pub(crate) const CHUNK_ENCODED_LEN: usize = 120;
+pub(crate) const CHUNK_DECODED_MAXLEN: usize = 90;
+
+// Decodes `input` chunk by chunk through a stack buffer, passing every
+// decoded chunk to `f`. Characters cannot be ignored.
+pub(crate) fn decode_chunks<D: Decoder, F: FnMut(&[u8]) -> Result<(), Error>>(
+    input: &[u8],
+    mut f: F,
+) -> Result<(), Error> {
+    let mut bin = [0u8; CHUNK_DECODED_MAXLEN];
+    let res = input
+        .chunks(CHUNK_ENCODED_LEN)
+        .try_for_each(|chunk| f(D::decode(&mut bin, chunk, None)?));
+    clear(&mut bin);
+    res
+}
+
+// Encodes the first `bin_len` bytes of `buf` over themselves. Chunks are
+// processed from the end, so that the output of a chunk only overwrites data
+// that has already been encoded. Chunk lengths are multiples of the group
+// size of every encoder, so only the last chunk can be padded.
+pub(crate) fn encode_in_place<E: EncoderExt>(
+    buf: &mut [u8],
+    bin_len: usize,
+) -> Result<&[u8], Error> {
+    let encoded_len = E::encoded_len(bin_len)?;
+    if buf.len() < encoded_len || buf.len() < bin_len {
+        return Err(Error::Overflow);
+    }
+    let mut chunk = [0u8; CHUNK_DECODED_MAXLEN];
+    let res = (0..bin_len)
+        .step_by(CHUNK_DECODED_MAXLEN)
+        .rev()
+        .try_for_each(|chunk_pos| {
+            let chunk_len = core::cmp::min(CHUNK_DECODED_MAXLEN, bin_len - chunk_pos);
+            chunk[..chunk_len].copy_from_slice(&buf[chunk_pos..chunk_pos + chunk_len]);
+            let encoded_pos = E::encoded_len(chunk_pos)?;
+            E::encode(&mut buf[encoded_pos..], &chunk[..chunk_len]).map(|_| ())
+        });
+    clear(&mut chunk);
+    res?;
+    Ok(&buf[..encoded_len])
+}
+
+// Encodes `bin` chunk by chunk through a stack buffer, passing every encoded
+// chunk to `f`. Only the last chunk can be padded.
+pub(crate) fn encode_chunks<E: EncoderExt, F: FnMut(&str) -> fmt::Result>(
+    bin: &[u8],
+    mut f: F,
+) -> fmt::Result {
+    let mut encoded = [0u8; 2 * CHUNK_DECODED_MAXLEN];
+    let res = bin.chunks(CHUNK_DECODED_MAXLEN).try_for_each(|chunk| {
+        let encoded = E::encode_to_str(&mut encoded, chunk).map_err(|_| fmt::Error)?;
+        f(encoded)
+    });
+    clear(&mut encoded);
+    res
+}
+
+/// Converts data encoded with one codec directly into another encoding.
+///
+/// The input is decoded and re-encoded in small chunks, so that the binary
+/// data never has to be fully stored in an intermediate buffer. The only
+/// temporary storage is a small stack buffer, which is cleared before returning.
+///
+/// Decoding applies the same strict validation as [`Decoder::decode`]; characters
+/// cannot be ignored. The whole input is validated before any chunk is decoded,
+/// so that padding is only accepted at the end. Like the codecs themselves, the
+/// conversion runs in constant time relative to the input length.
+///
+/// Validating first means that the input is read twice, so a conversion costs
+/// about as much as a call to [`DecoderExt::validate`] followed by a call to
+/// [`Decoder::decode`]. This is why `D` must implement [`DecoderExt`], and
+/// chunks are sized for the groups of the codecs of this crate, so `E` must
+/// implement [`EncoderExt`]. Both are implemented by every codec of the crate.
+///
+/// # Arguments
+///
+/// * `output` - Mutable buffer to store the re-encoded output
+/// * `input` - Input data, encoded with `D`
+///
+/// # Returns
+///
+/// * `Ok(&[u8])` - A slice of the output buffer containing the data encoded with `E`
+/// * `Err(Error::Overflow)` - If the output buffer is too small
+/// * `Err(Error::InvalidInput)` - If the input isn't valid for `D`
+///
+/// # Examples
+///
+/// ```
+/// use ct_codecs::{transcode, Base64, Base64UrlSafeNoPadding, Hex};
+///
+/// fn example() -> Result<(), ct_codecs::Error> {
+///     let mut b64 = [0u8; 20];
+///     let b64 = transcode::<Hex, Base64>(&mut b64, "48656c6c6f2c20776f726c6421")?;
+///     assert_eq!(b64, b"SGVsbG8sIHdvcmxkIQ==");
+///
+///     let mut b64url = [0u8; 4];
+///     let b64url = transcode::<Base64, Base64UrlSafeNoPadding>(&mut b64url, "+/+/")?;
+///     assert_eq!(b64url, b"-_-_");
+///     Ok(())
+/// }
+/// # example().unwrap();
+/// ```
+pub fn transcode<D: DecoderExt, E: EncoderExt>(
+    output: &mut [u8],
+    input: impl AsRef<[u8]>,
+) -> Result<&[u8], Error> {
+    let input = input.as_ref();
+    // Chunks are decoded independently, and each of them could end with padding.
+    D::validate(input, None)?;
+    let mut output_pos = 0usize;
+    decode_chunks::<D, _>(input, |bin| {
+        output_pos += E::encode(&mut output[output_pos..], bin)?.len();
+        Ok(())
+    })?;
+    Ok(&output[..output_pos])
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_transcode() {
+    use crate::*;
+
+    let bin: Vec<u8> = (0..1000u32).map(|i| (i * 7 + i / 3) as u8).collect();
+    for len in [0, 1, 2, 3, 4, 5, 59, 60, 61, 89, 90, 91, 300, 1000] {
+        let bin = &bin[..len];
+        let b64 = Base64::encode_to_string(bin).unwrap();
+        let b32 = Base32NoPadding::encode_to_string(bin).unwrap();
+        let hex = Hex::encode_to_string(bin).unwrap();
+        let mut out = vec![0u8; 2 * len + 16];
+
+        let res = transcode::<Base64, Base64UrlSafeNoPadding>(&mut out, &b64).unwrap();
+        assert_eq!(
+            res,
+            Base64UrlSafeNoPadding::encode_to_string(bin)
+                .unwrap()
+                .as_bytes()
+        );
+        let res = transcode::<Hex, Base64>(&mut out, &hex).unwrap();
+        assert_eq!(res, b64.as_bytes());
+        let res = transcode::<Base32NoPadding, Hex>(&mut out, &b32).unwrap();
+        assert_eq!(res, hex.as_bytes());
+        let res = transcode::<Base64, Base32HexNoPadding>(&mut out, &b64).unwrap();
+        assert_eq!(
+            res,
+            Base32HexNoPadding::encode_to_string(bin)
+                .unwrap()
+                .as_bytes()
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_transcode_errors() {
+    use crate::*;
+
+    let mut out = [0u8; 512];
+    let mut b64 = Base64::encode_to_string([0xa5u8; 200]).unwrap();
+    assert_eq!(
+        transcode::<Base64NoPadding, Hex>(&mut out, &b64),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        transcode::<Base64, Hex>(&mut out[..399], &b64),
+        Err(Error::Overflow)
+    );
+    b64.replace_range(150..151, "=");
+    assert_eq!(
+        transcode::<Base64, Hex>(&mut out, &b64),
+        Err(Error::InvalidInput)
+    );
+    assert_eq!(
+        transcode::<Hex, Base64>(&mut out, "abc"),
+        Err(Error::InvalidInput)
+    );
+
+    // Padding at the end of a chunk that isn't the last one.
+    let b64 = Base64::encode_to_string([0xa5u8; 88]).unwrap() + "aGk=";
+    assert_eq!(&b64[118..120], "==");
+    assert_eq!(Base64::decode_to_vec(&b64, None), Err(Error::InvalidInput));
+    assert_eq!(
+        transcode::<Base64, Hex>(&mut out, &b64),
+        Err(Error::InvalidInput)
+    );
+    let b32 = Base32::encode_to_string([0xa5u8; 74]).unwrap() + "NBUQ====";
+    assert_eq!(&b32[119..120], "=");
+    assert_eq!(
+        transcode::<Base32, Hex>(&mut out, &b32),
+        Err(Error::InvalidInput)
+    );
+}
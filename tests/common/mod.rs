@@ -0,0 +1,26 @@
+//! A small deterministic random number generator, shared by the tests, the
+//! benchmarks and the `fuzzing` module, so that runs are reproducible without
+//! any dependencies.
+
+#![allow(dead_code)]
+
+/// xorshift64*. The state must not be zero.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number lower than `n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    pub fn fill(&mut self, buf: &mut [u8]) {
+        buf.iter_mut().for_each(|x| *x = self.next() as u8);
+    }
+}
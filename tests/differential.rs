@@ -0,0 +1,759 @@
+//! Differential tests against a reference implementation.
+//!
+//! The reference codec below is table-driven and written for clarity, not for
+//! speed or constant-time execution. Every variant of the crate is compared
+//! with it on pseudo-random data of every length up to `MAX_LEN`, on every
+//! single-byte corruption of short encodings, with every combination of a
+//! small set of ignored characters, and on long inputs with padding at the end
+//! of inner chunks. The chunked functions (`transcode`, `verify_encoded` and
+//! `decode_either`) are compared along with the regular decoders.
+//!
+//! By default, lengths and corruptions are sampled. The exhaustive tests take
+//! minutes in debug builds, so they are ignored by default. Run them with:
+//!
+//! ```text
+//! cargo test --release --test differential -- --ignored
+//! ```
+//!
+//! The pseudo-random generator uses a fixed seed, so failures are
+//! reproducible. A failing input is shrunk before being reported, and printed
+//! as a call that can be pasted into a test.
+
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::Rng;
+use ct_codecs::*;
+
+const MAX_LEN: usize = 1024;
+
+// Encodings of binary data up to that length get every possible corruption.
+const CORRUPTION_MAX_LEN: usize = 16;
+
+// By default, every length up to that is tested, along with a sample of the
+// longer ones, and corruptions are sampled. The exhaustive tests are ignored.
+const SAMPLED_MAX_LEN: usize = 128;
+const SAMPLED_LONG_LENS: usize = 32;
+const SAMPLED_CORRUPTIONS: usize = 16;
+
+// Characters that can be ignored: whitespace, padding, an alphabet character
+// of every variant and a character of the URL-safe alphabets only.
+const IGNORE_POOL: [u8; 6] = [b' ', b'\n', b'=', b'A', b'-', b'\0'];
+
+// Length of the chunks the chunked functions decode independently.
+const CHUNK_ENCODED_LEN: usize = 120;
+
+fn random_bytes(rng: &mut Rng, len: usize) -> Vec<u8> {
+    let mut bin = vec![0u8; len];
+    rng.fill(&mut bin);
+    bin
+}
+
+/// A straightforward model of an encoding.
+struct Reference {
+    /// Characters, indexed by the value they encode.
+    alphabet: &'static [u8],
+    /// Additional characters accepted by the decoder, and their values.
+    aliases: &'static [(u8, u8)],
+    /// Number of bits encoded by a character.
+    bits: usize,
+    /// Number of characters padded encodings are a multiple of, or 0 if
+    /// the variant doesn't use padding.
+    block_len: usize,
+    /// Value of every character accepted by the decoder.
+    values: [Option<u8>; 256],
+}
+
+impl Reference {
+    fn new(
+        alphabet: &'static [u8],
+        aliases: &'static [(u8, u8)],
+        bits: usize,
+        block_len: usize,
+    ) -> Self {
+        let mut values = [None; 256];
+        for (v, &c) in alphabet.iter().enumerate() {
+            values[c as usize] = Some(v as u8);
+        }
+        for &(c, v) in aliases {
+            values[c as usize] = Some(v);
+        }
+        Reference {
+            alphabet,
+            aliases,
+            bits,
+            block_len,
+            values,
+        }
+    }
+
+    fn value(&self, c: u8) -> Option<u8> {
+        self.values[c as usize]
+    }
+
+    fn encode(&self, bin: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        let mut bits = Vec::with_capacity(bin.len() * 8);
+        for &x in bin {
+            bits.extend((0..8).rev().map(|i| (x >> i) & 1));
+        }
+        for symbol in bits.chunks(self.bits) {
+            let v = (0..self.bits).fold(0, |v, i| (v << 1) | symbol.get(i).copied().unwrap_or(0));
+            encoded.push(self.alphabet[v as usize]);
+        }
+        if self.block_len != 0 {
+            while encoded.len() % self.block_len != 0 {
+                encoded.push(b'=');
+            }
+        }
+        encoded
+    }
+
+    /// Decodes `encoded`, with an output buffer large enough for any input.
+    fn decode(&self, encoded: &[u8], ignore: Option<&[u8]>) -> Result<Vec<u8>, Error> {
+        let is_ignored = |c: u8| ignore.map_or(false, |ignore| ignore.contains(&c));
+        let mut values = Vec::new();
+        let mut end = encoded.len();
+        for (pos, &c) in encoded.iter().enumerate() {
+            match self.value(c) {
+                Some(v) => values.push(v),
+                None if is_ignored(c) => {}
+                None => {
+                    end = pos;
+                    break;
+                }
+            }
+        }
+
+        // The last character can't only encode padding bits, and these must be zero.
+        let extra_bits = values.len() * self.bits % 8;
+        if extra_bits >= self.bits {
+            return Err(Error::InvalidInput);
+        }
+        if let Some(&last) = values.last() {
+            if last & ((1 << extra_bits) - 1) != 0 {
+                return Err(Error::InvalidInput);
+            }
+        }
+
+        // The encoded data must be followed by the exact amount of padding,
+        // and nothing but ignored characters.
+        let mut padding_len = if self.block_len == 0 {
+            0
+        } else {
+            (self.block_len - values.len() % self.block_len) % self.block_len
+        };
+        if self.block_len == 0 && end != encoded.len() {
+            return Err(Error::InvalidInput);
+        }
+        for &c in &encoded[end..] {
+            if padding_len > 0 && c == b'=' {
+                padding_len -= 1;
+            } else if !is_ignored(c) {
+                return Err(Error::InvalidInput);
+            }
+        }
+        if padding_len > 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let bits: Vec<u8> = values
+            .iter()
+            .flat_map(|&v| (0..self.bits).rev().map(move |i| (v >> i) & 1))
+            .collect();
+        let bin: Vec<u8> = bits
+            .chunks_exact(8)
+            .map(|byte| byte.iter().fold(0, |x, &bit| (x << 1) | bit))
+            .collect();
+        Ok(bin)
+    }
+}
+
+/// The concatenated reference decodings of every chunk, if they all succeed.
+fn decode_chunks(reference: &Reference, encoded: &[u8]) -> Option<Vec<u8>> {
+    encoded
+        .chunks(CHUNK_ENCODED_LEN)
+        .try_fold(Vec::new(), |mut bin, chunk| {
+            bin.extend(reference.decode(chunk, None).ok()?);
+            Some(bin)
+        })
+}
+
+/// The result of decoding into a buffer of `bin_maxlen` bytes.
+fn truncated(decoded: &Result<Vec<u8>, Error>, bin_maxlen: usize) -> Result<Vec<u8>, Error> {
+    match decoded {
+        Ok(bin) if bin.len() > bin_maxlen => Err(Error::Overflow),
+        res => res.clone(),
+    }
+}
+
+const B64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const B64_URLSAFE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const B32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const B32_HEX: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+const HEX: &[u8] = b"0123456789abcdef";
+const HEX_UPPERCASE: &[(u8, u8)] = &[
+    (b'A', 10),
+    (b'B', 11),
+    (b'C', 12),
+    (b'D', 13),
+    (b'E', 14),
+    (b'F', 15),
+];
+
+type EncodeFn = fn(&mut [u8], &[u8]) -> Result<usize, Error>;
+type DecodeFn = fn(&mut [u8], &[u8], Option<&[u8]>) -> Result<usize, Error>;
+type ValidateFn = fn(&[u8], Option<&[u8]>) -> Result<usize, Error>;
+type TranscodeFn = fn(&mut [u8], &[u8]) -> Result<usize, Error>;
+type VerifyFn = fn(&[u8], &[u8]) -> bool;
+type DecodeEitherFn = fn(&mut [u8], &[u8]) -> Result<(usize, bool), Error>;
+
+/// A variant of the crate, and its reference.
+struct Variant {
+    name: &'static str,
+    reference: Reference,
+    /// `None` for decoders without a matching encoder.
+    encode: Option<EncodeFn>,
+    decode: DecodeFn,
+    decode_in_place: DecodeFn,
+    validate: ValidateFn,
+    /// Transcodes to hex.
+    transcode: TranscodeFn,
+    verify_encoded: VerifyFn,
+    /// Decodes with the variant as both `A` and `B`.
+    decode_either_a: DecodeEitherFn,
+    /// Decodes with a decoder rejecting everything as `A`, and the variant as `B`.
+    decode_either_b: DecodeEitherFn,
+}
+
+/// A decoder that rejects every input.
+struct Reject;
+
+impl Decoder for Reject {
+    fn decode<'t, IN: AsRef<[u8]>>(
+        _bin: &'t mut [u8],
+        _encoded: IN,
+        _ignore: Option<&[u8]>,
+    ) -> Result<&'t [u8], Error> {
+        Err(Error::InvalidInput)
+    }
+}
+
+fn encode<E: Encoder>(encoded: &mut [u8], bin: &[u8]) -> Result<usize, Error> {
+    E::encode(encoded, bin).map(|encoded| encoded.len())
+}
+
+fn decode<D: Decoder>(
+    bin: &mut [u8],
+    encoded: &[u8],
+    ignore: Option<&[u8]>,
+) -> Result<usize, Error> {
+    D::decode(bin, encoded, ignore).map(|bin| bin.len())
+}
+
+fn decode_in_place<D: DecoderExt>(
+    buf: &mut [u8],
+    _encoded: &[u8],
+    ignore: Option<&[u8]>,
+) -> Result<usize, Error> {
+    D::decode_in_place(buf, ignore).map(|bin| bin.len())
+}
+
+fn validate<D: DecoderExt>(encoded: &[u8], ignore: Option<&[u8]>) -> Result<usize, Error> {
+    D::validate(encoded, ignore)
+}
+
+fn transcode_to_hex<D: DecoderExt>(output: &mut [u8], input: &[u8]) -> Result<usize, Error> {
+    transcode::<D, Hex>(output, input).map(|output| output.len())
+}
+
+fn verify_encoded<D: DecoderExt>(encoded: &[u8], expected: &[u8]) -> bool {
+    ct_codecs::verify_encoded::<D, _>(encoded, expected)
+}
+
+fn decode_either<A: Decoder, B: DecoderExt>(
+    bin: &mut [u8],
+    encoded: &[u8],
+) -> Result<(usize, bool), Error> {
+    ct_codecs::decode_either::<A, B, _>(bin, encoded)
+        .map(|(bin, use_b)| (bin.len(), bool::from(use_b)))
+}
+
+macro_rules! variant {
+    ($codec:ty, $alphabet:expr, $aliases:expr, $bits:expr, $block_len:expr, $encode:expr) => {
+        Variant {
+            name: stringify!($codec),
+            reference: Reference::new($alphabet, $aliases, $bits, $block_len),
+            encode: $encode,
+            decode: decode::<$codec>,
+            decode_in_place: decode_in_place::<$codec>,
+            validate: validate::<$codec>,
+            transcode: transcode_to_hex::<$codec>,
+            verify_encoded: verify_encoded::<$codec>,
+            decode_either_a: decode_either::<$codec, $codec>,
+            decode_either_b: decode_either::<Reject, $codec>,
+        }
+    };
+    ($codec:ty, $alphabet:expr, $aliases:expr, $bits:expr, $block_len:expr) => {
+        variant!(
+            $codec,
+            $alphabet,
+            $aliases,
+            $bits,
+            $block_len,
+            Some(encode::<$codec>)
+        )
+    };
+}
+
+fn variants() -> Vec<Variant> {
+    const URLSAFE_ALIASES: &[(u8, u8)] = &[(b'-', 62), (b'_', 63)];
+    vec![
+        variant!(Base64, B64, &[], 6, 4),
+        variant!(Base64NoPadding, B64, &[], 6, 0),
+        variant!(Base64UrlSafe, B64_URLSAFE, &[], 6, 4),
+        variant!(Base64UrlSafeNoPadding, B64_URLSAFE, &[], 6, 0),
+        variant!(Base64Mixed, B64, URLSAFE_ALIASES, 6, 4, None),
+        variant!(Base64MixedNoPadding, B64, URLSAFE_ALIASES, 6, 0, None),
+        variant!(Base32, B32, &[], 5, 8),
+        variant!(Base32NoPadding, B32, &[], 5, 0),
+        variant!(Base32Hex, B32_HEX, &[], 5, 8),
+        variant!(Base32HexNoPadding, B32_HEX, &[], 5, 0),
+        variant!(Hex, HEX, HEX_UPPERCASE, 4, 0),
+    ]
+}
+
+/// Reference encodings to decode: the canonical one, and for variants
+/// accepting aliases, one using them.
+fn encodings(variant: &Variant, bin: &[u8]) -> Vec<Vec<u8>> {
+    let encoded = variant.reference.encode(bin);
+    let aliased: Vec<u8> = encoded
+        .iter()
+        .map(|&c| {
+            let v = variant.reference.value(c);
+            variant
+                .reference
+                .aliases
+                .iter()
+                .find(|&&(_, x)| Some(x) == v)
+                .map_or(c, |&(alias, _)| alias)
+        })
+        .collect();
+    if aliased == encoded {
+        vec![encoded]
+    } else {
+        vec![encoded, aliased]
+    }
+}
+
+/// Removes characters from `input` as long as `fails` keeps returning `true`.
+fn shrink(mut input: Vec<u8>, fails: impl Fn(&[u8]) -> bool) -> Vec<u8> {
+    let mut pos = 0;
+    while pos < input.len() {
+        let mut candidate = input.clone();
+        candidate.remove(pos);
+        if fails(&candidate) {
+            input = candidate;
+        } else {
+            pos += 1;
+        }
+    }
+    input
+}
+
+fn ignore_repr(ignore: Option<&[u8]>) -> String {
+    match ignore {
+        None => "None".to_string(),
+        Some(ignore) => format!("Some(b\"{}\")", ignore.escape_ascii()),
+    }
+}
+
+/// Compares the encoder with the reference, with exact and larger output buffers.
+fn check_encode(variant: &Variant, bin: &[u8]) {
+    let Some(encode) = variant.encode else {
+        return;
+    };
+    let run = |bin: &[u8], encoded_maxlen: usize| {
+        let mut encoded = vec![0u8; encoded_maxlen];
+        encode(&mut encoded, bin).map(|len| encoded[..len].to_vec())
+    };
+    let fails = |bin: &[u8]| {
+        let expected = Ok(variant.reference.encode(bin));
+        let encoded_len = expected.as_ref().map_or(0, |e| e.len());
+        run(bin, encoded_len) != expected || run(bin, encoded_len + 3) != expected
+    };
+    if fails(bin) {
+        let bin = shrink(bin.to_vec(), fails);
+        let expected = variant.reference.encode(&bin);
+        let mut encoded_maxlen = expected.len();
+        if run(&bin, encoded_maxlen) == Ok(expected.clone()) {
+            encoded_maxlen += 3;
+        }
+        panic!(
+            "{}::encode(&mut [0u8; {}], b\"{}\"): expected {:?}, got {:?}",
+            variant.name,
+            encoded_maxlen,
+            bin.escape_ascii(),
+            String::from_utf8(expected).unwrap(),
+            run(&bin, encoded_maxlen).map(|e| String::from_utf8_lossy(&e).into_owned()),
+        );
+    }
+    if !bin.is_empty() {
+        let encoded_len = variant.reference.encode(bin).len();
+        let mut encoded = vec![0u8; encoded_len - 1];
+        assert_eq!(
+            encode(&mut encoded, bin),
+            Err(Error::Overflow),
+            "{}::encode(&mut [0u8; {}], b\"{}\")",
+            variant.name,
+            encoded_len - 1,
+            bin.escape_ascii(),
+        );
+    }
+}
+
+/// Compares the results of every decoding function with the reference.
+fn decode_mismatch(
+    variant: &Variant,
+    encoded: &[u8],
+    ignore: Option<&[u8]>,
+    bin_maxlen: usize,
+) -> Option<String> {
+    let decoded = variant.reference.decode(encoded, ignore);
+    let expected = truncated(&decoded, bin_maxlen);
+
+    let mut bin = vec![0u8; bin_maxlen];
+    let res = (variant.decode)(&mut bin, encoded, ignore).map(|len| bin[..len].to_vec());
+    if res != expected {
+        return Some(format!(
+            "{}::decode(&mut [0u8; {}], b\"{}\", {}): expected {:?}, got {:?}",
+            variant.name,
+            bin_maxlen,
+            encoded.escape_ascii(),
+            ignore_repr(ignore),
+            expected,
+            res
+        ));
+    }
+
+    let res = (variant.validate)(encoded, ignore);
+    if res != decoded.as_ref().map(|bin| bin.len()).map_err(|&e| e) {
+        return Some(format!(
+            "{}::validate(b\"{}\", {}): expected {:?}, got {:?}",
+            variant.name,
+            encoded.escape_ascii(),
+            ignore_repr(ignore),
+            decoded.map(|bin| bin.len()),
+            res
+        ));
+    }
+
+    let mut buf = encoded.to_vec();
+    let res = (variant.decode_in_place)(&mut buf, encoded, ignore).map(|len| buf[..len].to_vec());
+    if res != decoded {
+        return Some(format!(
+            "{}::decode_in_place(&mut *b\"{}\", {}): expected {:?}, got {:?}",
+            variant.name,
+            encoded.escape_ascii(),
+            ignore_repr(ignore),
+            decoded,
+            res
+        ));
+    }
+
+    // The chunked functions don't ignore characters.
+    if ignore.is_some() {
+        return None;
+    }
+
+    let mut hex = vec![0u8; bin_maxlen * 2];
+    let res = (variant.transcode)(&mut hex, encoded).map(|len| hex[..len].to_vec());
+    let expected_hex = expected
+        .as_ref()
+        .map(|bin| Hex::encode_to_string(bin).unwrap().into_bytes())
+        .map_err(|&e| e);
+    if res != expected_hex {
+        return Some(format!(
+            "transcode::<{}, Hex>(&mut [0u8; {}], b\"{}\"): expected {:?}, got {:?}",
+            variant.name,
+            bin_maxlen * 2,
+            encoded.escape_ascii(),
+            expected_hex.map(|hex| String::from_utf8(hex).unwrap()),
+            res.map(|hex| String::from_utf8_lossy(&hex).into_owned()),
+        ));
+    }
+
+    // Expected data that would match if chunks were only checked one by one.
+    let candidate = decoded
+        .clone()
+        .ok()
+        .or_else(|| decode_chunks(&variant.reference, encoded))
+        .unwrap_or_default();
+    let res = (variant.verify_encoded)(encoded, &candidate);
+    if res != decoded.is_ok() {
+        return Some(format!(
+            "verify_encoded::<{}, _>(b\"{}\", b\"{}\"): expected {}, got {}",
+            variant.name,
+            encoded.escape_ascii(),
+            candidate.escape_ascii(),
+            decoded.is_ok(),
+            res
+        ));
+    }
+    if let Ok(bin) = &decoded {
+        let mut wrong = bin.clone();
+        match wrong.last_mut() {
+            Some(last) => *last ^= 1,
+            None => wrong.push(0),
+        }
+        if (variant.verify_encoded)(encoded, &wrong) {
+            return Some(format!(
+                "verify_encoded::<{}, _>(b\"{}\", b\"{}\"): expected false, got true",
+                variant.name,
+                encoded.escape_ascii(),
+                wrong.escape_ascii(),
+            ));
+        }
+    }
+
+    for (decode_either, use_b, call) in [
+        (
+            variant.decode_either_a,
+            false,
+            "decode_either::<{0}, {0}, _>",
+        ),
+        (
+            variant.decode_either_b,
+            true,
+            "decode_either::<Reject, {0}, _>",
+        ),
+    ] {
+        let mut bin = vec![0u8; bin_maxlen];
+        let res = decode_either(&mut bin, encoded).map(|(len, b)| (bin[..len].to_vec(), b));
+        let expected = expected.clone().map(|bin| (bin, use_b));
+        if res != expected {
+            return Some(format!(
+                "{}(&mut [0u8; {}], b\"{}\"): expected {:?}, got {:?}",
+                call.replace("{0}", variant.name),
+                bin_maxlen,
+                encoded.escape_ascii(),
+                expected,
+                res
+            ));
+        }
+    }
+    None
+}
+
+// Checks a decoding with an output buffer large enough for any input, and
+// reports a shrunk reproducer on failure.
+fn check_decode(variant: &Variant, encoded: &[u8], ignore: Option<&[u8]>) {
+    let fails = |encoded: &[u8]| decode_mismatch(variant, encoded, ignore, encoded.len()).is_some();
+    if fails(encoded) {
+        let encoded = shrink(encoded.to_vec(), fails);
+        panic!(
+            "{}",
+            decode_mismatch(variant, &encoded, ignore, encoded.len()).unwrap()
+        );
+    }
+}
+
+/// Lengths of binary data to test: all of them up to `MAX_LEN`, or a sample.
+fn lengths(rng: &mut Rng, exhaustive: bool) -> Vec<usize> {
+    if exhaustive {
+        return (0..=MAX_LEN).collect();
+    }
+    let long_lens =
+        (0..SAMPLED_LONG_LENS).map(|_| SAMPLED_MAX_LEN + 1 + rng.below(MAX_LEN - SAMPLED_MAX_LEN));
+    (0..=SAMPLED_MAX_LEN)
+        .chain(long_lens.collect::<Vec<_>>())
+        .collect()
+}
+
+fn roundtrip(exhaustive: bool) {
+    let mut rng = Rng(0x6a09_e667_f3bc_c908);
+    for variant in variants() {
+        for len in lengths(&mut rng, exhaustive) {
+            let bin = random_bytes(&mut rng, len);
+            check_encode(&variant, &bin);
+            for encoded in encodings(&variant, &bin) {
+                if let Some(mismatch) = decode_mismatch(&variant, &encoded, None, len) {
+                    panic!("{}", mismatch);
+                }
+                if len > 0 {
+                    let mut bin = vec![0u8; len - 1];
+                    assert_eq!(
+                        (variant.decode)(&mut bin, &encoded, None),
+                        Err(Error::Overflow),
+                        "{}::decode(&mut [0u8; {}], b\"{}\", None)",
+                        variant.name,
+                        len - 1,
+                        encoded.escape_ascii(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn corruptions(exhaustive: bool) {
+    let mut rng = Rng(0xbb67_ae85_84ca_a73b);
+    for variant in variants() {
+        for len in 0..=CORRUPTION_MAX_LEN {
+            let bin = random_bytes(&mut rng, len);
+            for encoded in encodings(&variant, &bin) {
+                for pos in 0..encoded.len() {
+                    let bytes: Vec<u8> = if exhaustive {
+                        (0..=255).collect()
+                    } else {
+                        random_bytes(&mut rng, SAMPLED_CORRUPTIONS)
+                    };
+                    for c in bytes {
+                        let mut corrupted = encoded.clone();
+                        corrupted[pos] = c;
+                        check_decode(&variant, &corrupted, None);
+                    }
+                }
+            }
+        }
+        // Random corruptions of longer encodings.
+        for _ in 0..if exhaustive { 2000 } else { 100 } {
+            let len = CORRUPTION_MAX_LEN + rng.below(MAX_LEN - CORRUPTION_MAX_LEN);
+            let mut encoded = variant.reference.encode(&random_bytes(&mut rng, len));
+            let pos = rng.below(encoded.len());
+            encoded[pos] = rng.next() as u8;
+            check_decode(&variant, &encoded, None);
+        }
+    }
+}
+
+#[test]
+fn test_differential_roundtrip() {
+    roundtrip(false);
+}
+
+#[test]
+#[ignore]
+fn test_differential_roundtrip_exhaustive() {
+    roundtrip(true);
+}
+
+#[test]
+fn test_differential_corruptions() {
+    corruptions(false);
+}
+
+#[test]
+#[ignore]
+fn test_differential_corruptions_exhaustive() {
+    corruptions(true);
+}
+
+#[test]
+fn test_differential_ignore() {
+    let mut rng = Rng(0x3c6e_f372_fe94_f82b);
+    let ignore_sets: Vec<Vec<u8>> = (0..1u32 << IGNORE_POOL.len())
+        .map(|set| {
+            IGNORE_POOL
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| set & (1 << i) != 0)
+                .map(|(_, &c)| c)
+                .collect()
+        })
+        .collect();
+    for variant in variants() {
+        for len in 0..=64 {
+            let bin = random_bytes(&mut rng, len);
+            for encoded in encodings(&variant, &bin) {
+                // Inserts characters from the pool at random positions,
+                // including before and after the padding.
+                let mut noisy = encoded.clone();
+                for _ in 0..rng.below(4) {
+                    let pos = rng.below(noisy.len() + 1);
+                    noisy.insert(pos, IGNORE_POOL[rng.below(IGNORE_POOL.len())]);
+                }
+                for input in [&encoded, &noisy] {
+                    check_decode(&variant, input, None);
+                    for ignore in &ignore_sets {
+                        check_decode(&variant, input, Some(ignore));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_differential_chunk_padding() {
+    let mut rng = Rng(0xa54f_f53a_5f1d_36f1);
+    for variant in variants() {
+        // Concatenated encodings, with the first one ending at, before or
+        // after a chunk boundary.
+        for len in 0..=100 {
+            let mut encoded = variant.reference.encode(&random_bytes(&mut rng, len));
+            let tail_len = rng.below(200);
+            encoded.extend(variant.reference.encode(&random_bytes(&mut rng, tail_len)));
+            check_decode(&variant, &encoded, None);
+        }
+        // Padding characters overwriting the end of an inner chunk.
+        for _ in 0..200 {
+            let len = CHUNK_ENCODED_LEN + rng.below(MAX_LEN - CHUNK_ENCODED_LEN);
+            let mut encoded = variant.reference.encode(&random_bytes(&mut rng, len));
+            let chunks = (encoded.len() - 1) / CHUNK_ENCODED_LEN;
+            let end = CHUNK_ENCODED_LEN * (1 + rng.below(chunks));
+            let padding_len = 1 + rng.below(6);
+            encoded[end - padding_len..end].fill(b'=');
+            check_decode(&variant, &encoded, None);
+        }
+    }
+}
+
+#[test]
+fn test_reference() {
+    // RFC 4648 test vectors, to make sure the reference itself is right.
+    let vectors = variants();
+    let find = |name: &str| vectors.iter().find(|v| v.name == name).unwrap();
+    let b64 = &find("Base64").reference;
+    let b32 = &find("Base32").reference;
+    let b32_hex = &find("Base32Hex").reference;
+    let hex = &find("Hex").reference;
+    for (bin, b64_expected, b32_expected, b32_hex_expected) in [
+        ("", "", "", ""),
+        ("f", "Zg==", "MY======", "CO======"),
+        ("fo", "Zm8=", "MZXQ====", "CPNG===="),
+        ("foo", "Zm9v", "MZXW6===", "CPNMU==="),
+        ("foob", "Zm9vYg==", "MZXW6YQ=", "CPNMUOG="),
+        ("fooba", "Zm9vYmE=", "MZXW6YTB", "CPNMUOJ1"),
+        ("foobar", "Zm9vYmFy", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+    ] {
+        for (reference, expected) in [
+            (b64, b64_expected),
+            (b32, b32_expected),
+            (b32_hex, b32_hex_expected),
+        ] {
+            assert_eq!(reference.encode(bin.as_bytes()), expected.as_bytes());
+            assert_eq!(
+                reference.decode(expected.as_bytes(), None),
+                Ok(bin.as_bytes().to_vec())
+            );
+        }
+    }
+    assert_eq!(hex.encode(b"\x01\xab"), b"01ab");
+    assert_eq!(hex.decode(b"01AB", None), Ok(vec![0x01, 0xab]));
+    assert_eq!(b64.decode(b"Zh==", None), Err(Error::InvalidInput));
+    assert_eq!(b64.decode(b"Zg=", None), Err(Error::InvalidInput));
+    assert_eq!(b64.decode(b"Zg== ", Some(b" ")), Ok(b"f".to_vec()));
+    assert_eq!(
+        truncated(&b64.decode(b"Zm9v", None), 2),
+        Err(Error::Overflow)
+    );
+}
+
+#[test]
+fn test_shrink() {
+    let input = b"xxaxxbxx".to_vec();
+    let fails = |input: &[u8]| input.contains(&b'a') && input.contains(&b'b');
+    assert_eq!(shrink(input, fails), b"ab");
+}
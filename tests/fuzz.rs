@@ -0,0 +1,74 @@
+//! Deterministic fuzzing of every codec, with the targets of the `fuzzing`
+//! module.
+//!
+//! The corpus starts with valid encodings, with and without padding,
+//! surrounded by characters that can be ignored, so that mutations mostly
+//! exercise the end of the inputs: padding, ignored characters after the data,
+//! and invalid characters stopping the decoding early.
+//!
+//! The number of inputs per codec can be changed with the
+//! `CT_CODECS_FUZZ_ITERATIONS` environment variable, and the seed with
+//! `CT_CODECS_FUZZ_SEED`. The same targets can be run with coverage-guided
+//! fuzzing, using `cargo fuzz` in the `fuzz/` directory.
+
+#![cfg(feature = "std")]
+
+use ct_codecs::*;
+
+const DEFAULT_ITERATIONS: usize = 5_000;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(default)
+}
+
+// Builds seeds from the encodings of short inputs, prefixed with every
+// selector of characters to ignore, and with ignored characters added to the
+// padding.
+fn seed_corpus<E: Encoder>() -> Vec<Vec<u8>> {
+    let mut seeds = Vec::new();
+    for len in 0..=12 {
+        let bin: Vec<u8> = (0..len as u8).map(|i| i.wrapping_mul(37) ^ 0xa5).collect();
+        let encoded = E::encode_to_string(&bin).unwrap().into_bytes();
+        for selector in 0..4u8 {
+            let mut seed = vec![selector];
+            seed.extend_from_slice(&encoded);
+            seeds.push(seed.clone());
+            if let Some(pos) = seed.iter().skip(1).position(|&c| c == b'=') {
+                seed.insert(pos + 2, b' ');
+                seed.push(b'\n');
+                seeds.push(seed);
+            }
+        }
+        seeds.push(bin);
+    }
+    seeds
+}
+
+fn run(target: fn(&[u8]), corpus: Vec<Vec<u8>>, seed: u64) {
+    let seeds: Vec<&[u8]> = corpus.iter().map(Vec::as_slice).collect();
+    let iterations = env_or("CT_CODECS_FUZZ_ITERATIONS", DEFAULT_ITERATIONS);
+    let seed = env_or("CT_CODECS_FUZZ_SEED", seed);
+    fuzzing::drive(target, &seeds, iterations, seed);
+}
+
+#[test]
+fn fuzz_base64() {
+    let mut corpus = seed_corpus::<Base64>();
+    corpus.extend(seed_corpus::<Base64UrlSafeNoPadding>());
+    run(fuzzing::base64, corpus, 0x510e_527f_ade6_82d1);
+}
+
+#[test]
+fn fuzz_base32() {
+    let mut corpus = seed_corpus::<Base32>();
+    corpus.extend(seed_corpus::<Base32HexNoPadding>());
+    run(fuzzing::base32, corpus, 0x9b05_688c_2b3e_6c1f);
+}
+
+#[test]
+fn fuzz_hex() {
+    run(fuzzing::hex, seed_corpus::<Hex>(), 0x1f83_d9ab_fb41_bd6b);
+}
@@ -0,0 +1,254 @@
+//! Statistical timing leak detection, following the dudect methodology.
+//!
+//! Every test times an operation on inputs from two classes, in a random
+//! order, and runs Welch's t-test on the two timing distributions, both on all
+//! the measurements and on measurements cropped at several percentiles to get
+//! rid of outliers. A constant-time operation keeps |t| below the threshold.
+//!
+//! These tests are slow and need a quiet machine, so they are ignored by
+//! default. Run them with:
+//!
+//! ```text
+//! cargo test --release --test timing -- --ignored --test-threads=1
+//! ```
+//!
+//! The number of measurements can be changed with the
+//! `CT_CODECS_TIMING_SAMPLES` environment variable.
+
+#![cfg(feature = "std")]
+// `black_box()` needs a more recent Rust version than the library.
+#![allow(clippy::incompatible_msrv)]
+
+mod common;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use common::Rng;
+use ct_codecs::*;
+
+// |t| values above that are considered as evidence of a timing leak.
+const T_THRESHOLD: f64 = 10.0;
+
+const DEFAULT_SAMPLES: usize = 200_000;
+
+// Measurements used to compute the cropping thresholds.
+const PILOT_SAMPLES: usize = 10_000;
+
+const INPUT_LEN: usize = 512;
+
+// Distinct buffers per class. The fixed class also uses as many copies of its
+// input, so that both classes have the same cache footprint.
+const INPUT_SETS: usize = 256;
+
+/// Online mean and variance of a series of measurements.
+#[derive(Clone, Copy, Default)]
+struct Moments {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+/// Welch's t-test between two classes of measurements.
+#[derive(Clone, Copy, Default)]
+struct TTest([Moments; 2]);
+
+impl TTest {
+    fn push(&mut self, class: usize, x: f64) {
+        self.0[class].push(x);
+    }
+
+    fn t(&self) -> f64 {
+        let [a, b] = self.0;
+        if a.n < 2.0 || b.n < 2.0 {
+            return 0.0;
+        }
+        let se = (a.variance() / a.n + b.variance() / b.n).sqrt();
+        if se == 0.0 {
+            return 0.0;
+        }
+        (a.mean - b.mean) / se
+    }
+}
+
+fn samples() -> usize {
+    std::env::var("CT_CODECS_TIMING_SAMPLES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_SAMPLES)
+}
+
+/// Times `op` on inputs of class 0 and class 1, and returns the largest |t|.
+///
+/// `inputs` holds a set of inputs for each class; an input is picked at
+/// random for every measurement.
+fn max_t<F: FnMut(&[u8])>(name: &str, inputs: [&[Vec<u8>]; 2], mut op: F) -> f64 {
+    let mut rng = Rng(0x0123_4567_89ab_cdef);
+    let mut measure = |rng: &mut Rng| {
+        let class = (rng.next() & 1) as usize;
+        let input = &inputs[class][rng.next() as usize % inputs[class].len()];
+        let start = Instant::now();
+        op(black_box(input));
+        (class, start.elapsed().as_nanos() as f64)
+    };
+
+    let mut pilot: Vec<f64> = (0..PILOT_SAMPLES).map(|_| measure(&mut rng).1).collect();
+    pilot.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let thresholds: Vec<f64> = (1..10)
+        .map(|i| 1.0 - 0.5f64.powi(i))
+        .map(|p| pilot[(p * PILOT_SAMPLES as f64) as usize])
+        .collect();
+
+    let mut full = TTest::default();
+    let mut cropped = vec![TTest::default(); thresholds.len()];
+    for _ in 0..samples() {
+        let (class, t) = measure(&mut rng);
+        full.push(class, t);
+        for (test, &threshold) in cropped.iter_mut().zip(&thresholds) {
+            if t < threshold {
+                test.push(class, t);
+            }
+        }
+    }
+    let max_t = cropped
+        .iter()
+        .map(TTest::t)
+        .chain(Some(full.t()))
+        .fold(0.0f64, |max, t| max.max(t.abs()));
+    println!("{}: max |t| = {:.2}", name, max_t);
+    max_t
+}
+
+fn assert_no_leak<F: FnMut(&[u8])>(name: &str, inputs: [&[Vec<u8>]; 2], op: F) {
+    let t = max_t(name, inputs, op);
+    assert!(
+        t < T_THRESHOLD,
+        "{}: timing leak detected (|t| = {:.2} >= {})",
+        name,
+        t,
+        T_THRESHOLD
+    );
+}
+
+fn random_inputs(rng: &mut Rng, count: usize) -> Vec<Vec<u8>> {
+    (0..count)
+        .map(|_| {
+            let mut input = vec![0u8; INPUT_LEN];
+            rng.fill(&mut input);
+            input
+        })
+        .collect()
+}
+
+fn encoded_inputs<E: Encoder>(bins: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    bins.iter()
+        .map(|bin| E::encode_to_string(bin).unwrap().into_bytes())
+        .collect()
+}
+
+// Fixed class: all-zero data; random class: random data.
+fn test_encoder<E: Encoder>(name: &str) {
+    let mut rng = Rng(1);
+    let fixed = vec![vec![0u8; INPUT_LEN]; INPUT_SETS];
+    let random = random_inputs(&mut rng, INPUT_SETS);
+    let mut encoded = vec![0u8; E::encoded_len(INPUT_LEN).unwrap()];
+    assert_no_leak(name, [&fixed, &random], |bin| {
+        black_box(E::encode(&mut encoded, bin).unwrap());
+    });
+}
+
+// Fixed class: encoded all-zero data; random class: encoded random data.
+fn test_decoder<E: Encoder, D: Decoder>(name: &str) {
+    let mut rng = Rng(2);
+    let fixed = encoded_inputs::<E>(&vec![vec![0u8; INPUT_LEN]; INPUT_SETS]);
+    let random = encoded_inputs::<E>(&random_inputs(&mut rng, INPUT_SETS));
+    let mut bin = vec![0u8; INPUT_LEN];
+    assert_no_leak(name, [&fixed, &random], |encoded| {
+        black_box(D::decode(&mut bin, encoded, None).unwrap());
+    });
+}
+
+// Valid class: encoded random data; invalid class: the same strings with
+// their last character replaced by `invalid`, so that every character is
+// still processed.
+fn test_decoder_validity<E: Encoder, D: Decoder>(name: &str, invalid: u8) {
+    let mut rng = Rng(3);
+    let valid = encoded_inputs::<E>(&random_inputs(&mut rng, INPUT_SETS));
+    let invalid: Vec<Vec<u8>> = valid
+        .iter()
+        .map(|encoded| {
+            let mut encoded = encoded.clone();
+            *encoded.last_mut().unwrap() = invalid;
+            assert!(D::decode_to_vec(&encoded, None).is_err());
+            encoded
+        })
+        .collect();
+    let mut bin = vec![0u8; INPUT_LEN];
+    assert_no_leak(name, [&valid, &invalid], |encoded| {
+        let _ = black_box(D::decode(&mut bin, encoded, None));
+    });
+}
+
+#[test]
+#[ignore]
+fn timing_base64() {
+    test_encoder::<Base64>("Base64::encode");
+    test_encoder::<Base64UrlSafeNoPadding>("Base64UrlSafeNoPadding::encode");
+    test_decoder::<Base64, Base64>("Base64::decode");
+    test_decoder::<Base64UrlSafeNoPadding, Base64UrlSafeNoPadding>(
+        "Base64UrlSafeNoPadding::decode",
+    );
+    test_decoder::<Base64, Base64Mixed>("Base64Mixed::decode");
+    test_decoder_validity::<Base64UrlSafeNoPadding, Base64UrlSafeNoPadding>(
+        "Base64UrlSafeNoPadding::decode (valid/invalid)",
+        b'!',
+    );
+}
+
+#[test]
+#[ignore]
+fn timing_base32() {
+    test_encoder::<Base32>("Base32::encode");
+    test_encoder::<Base32HexNoPadding>("Base32HexNoPadding::encode");
+    test_decoder::<Base32, Base32>("Base32::decode");
+    test_decoder::<Base32HexNoPadding, Base32HexNoPadding>("Base32HexNoPadding::decode");
+    test_decoder_validity::<Base32NoPadding, Base32NoPadding>(
+        "Base32NoPadding::decode (valid/invalid)",
+        b'!',
+    );
+}
+
+#[test]
+#[ignore]
+fn timing_hex() {
+    test_encoder::<Hex>("Hex::encode");
+    test_decoder::<Hex, Hex>("Hex::decode");
+    test_decoder_validity::<Hex, Hex>("Hex::decode (valid/invalid)", b'g');
+}
+
+#[test]
+fn test_welch_t_test() {
+    let mut test = TTest::default();
+    for i in 0..1000 {
+        test.push(0, (i % 10) as f64);
+        test.push(1, (i % 10) as f64);
+    }
+    assert_eq!(test.t(), 0.0);
+    for i in 0..1000 {
+        test.push(1, (i % 10) as f64 + 5.0);
+    }
+    assert!(test.t() < -T_THRESHOLD);
+}
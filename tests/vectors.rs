@@ -0,0 +1,214 @@
+//! Test vectors from `tests/vectors/`.
+//!
+//! Every file contains one section per encoder/decoder type, with valid
+//! vectors and invalid encodings that must be rejected. The valid vectors
+//! include the RFC 4648 section 10 test vectors, and vectors covering the whole
+//! alphabet of every variant. Vectors with characters to ignore, or marked as
+//! `decode_only`, are only checked against the decoder.
+//!
+//! Every section must be checked against a type, and every type must have a
+//! section.
+
+#![cfg(feature = "std")]
+
+use std::collections::BTreeMap;
+
+use ::serde::Deserialize;
+use ct_codecs::*;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Section {
+    valid: Vec<Valid>,
+    invalid: Vec<Invalid>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Valid {
+    /// Binary data, hex-encoded.
+    bin: String,
+    encoded: String,
+    #[serde(default)]
+    ignore: Option<String>,
+    /// Set for encodings the decoder accepts, but the encoder doesn't produce.
+    #[serde(default)]
+    decode_only: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Invalid {
+    encoded: String,
+    #[serde(default)]
+    ignore: Option<String>,
+    reason: String,
+}
+
+fn load() -> BTreeMap<String, Section> {
+    let mut sections = BTreeMap::new();
+    for (file, json) in [
+        ("base64.json", include_str!("vectors/base64.json")),
+        ("base32.json", include_str!("vectors/base32.json")),
+        ("base16.json", include_str!("vectors/base16.json")),
+    ] {
+        let file_sections: BTreeMap<String, Section> =
+            serde_json::from_str(json).unwrap_or_else(|e| panic!("{}: {}", file, e));
+        for (name, section) in file_sections {
+            assert!(
+                sections.insert(name.clone(), section).is_none(),
+                "duplicate section {}",
+                name
+            );
+        }
+    }
+    sections
+}
+
+// Parses the binary data of a vector without depending on the decoders under test.
+fn parse_bin(bin: &str) -> Vec<u8> {
+    assert_eq!(bin.len() % 2, 0, "odd length binary data: {}", bin);
+    (0..bin.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&bin[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn check_decoder<D: DecoderExt>(name: &str, section: &Section) {
+    for vector in &section.valid {
+        let expected = parse_bin(&vector.bin);
+        let encoded = vector.encoded.as_bytes();
+        let ignore = vector.ignore.as_deref().map(str::as_bytes);
+        let ctx = format!(
+            "{} {:?} (ignore: {:?})",
+            name, vector.encoded, vector.ignore
+        );
+
+        let mut bin = vec![0u8; expected.len()];
+        assert_eq!(
+            D::decode(&mut bin, encoded, ignore).as_deref(),
+            Ok(&expected[..]),
+            "{}",
+            ctx
+        );
+        assert_eq!(
+            D::decode_to_vec(encoded, ignore),
+            Ok(expected.clone()),
+            "{}",
+            ctx
+        );
+        assert_eq!(D::validate(encoded, ignore), Ok(expected.len()), "{}", ctx);
+        let mut buf = encoded.to_vec();
+        assert_eq!(
+            D::decode_in_place(&mut buf, ignore).as_deref(),
+            Ok(&expected[..]),
+            "{}",
+            ctx
+        );
+        if !expected.is_empty() {
+            let mut bin = vec![0u8; expected.len() - 1];
+            assert_eq!(
+                D::decode(&mut bin, encoded, ignore),
+                Err(Error::Overflow),
+                "{}",
+                ctx
+            );
+        }
+    }
+    for vector in &section.invalid {
+        let encoded = vector.encoded.as_bytes();
+        let ignore = vector.ignore.as_deref().map(str::as_bytes);
+        let ctx = format!(
+            "{} {:?} (ignore: {:?}) should be rejected: {}",
+            name, vector.encoded, vector.ignore, vector.reason
+        );
+
+        let mut bin = vec![0u8; encoded.len()];
+        assert_eq!(
+            D::decode(&mut bin, encoded, ignore),
+            Err(Error::InvalidInput),
+            "{}",
+            ctx
+        );
+        assert_eq!(
+            D::validate(encoded, ignore),
+            Err(Error::InvalidInput),
+            "{}",
+            ctx
+        );
+        let mut buf = encoded.to_vec();
+        assert_eq!(
+            D::decode_in_place(&mut buf, ignore),
+            Err(Error::InvalidInput),
+            "{}",
+            ctx
+        );
+    }
+}
+
+fn check_encoder<E: Encoder>(name: &str, section: &Section) {
+    let mut checked = 0;
+    for vector in &section.valid {
+        if vector.decode_only || vector.ignore.is_some() {
+            continue;
+        }
+        let bin = parse_bin(&vector.bin);
+        let ctx = format!("{} {:?}", name, vector.bin);
+
+        assert_eq!(
+            E::encoded_len(bin.len()),
+            Ok(vector.encoded.len()),
+            "{}",
+            ctx
+        );
+        let mut encoded = vec![0u8; vector.encoded.len()];
+        assert_eq!(
+            E::encode_to_str(&mut encoded, &bin),
+            Ok(vector.encoded.as_str()),
+            "{}",
+            ctx
+        );
+        assert_eq!(
+            E::encode_to_string(&bin),
+            Ok(vector.encoded.clone()),
+            "{}",
+            ctx
+        );
+        checked += 1;
+    }
+    assert!(checked > 0, "{}: no vectors for the encoder", name);
+}
+
+macro_rules! check {
+    ($sections:expr, decoder: $($decoder:ident),*; codec: $($codec:ident),*) => {
+        $(
+            let section = $sections
+                .remove(stringify!($decoder))
+                .expect(concat!("missing section: ", stringify!($decoder)));
+            check_decoder::<$decoder>(stringify!($decoder), &section);
+        )*
+        $(
+            let section = $sections
+                .remove(stringify!($codec))
+                .expect(concat!("missing section: ", stringify!($codec)));
+            check_decoder::<$codec>(stringify!($codec), &section);
+            check_encoder::<$codec>(stringify!($codec), &section);
+        )*
+    };
+}
+
+#[test]
+fn test_vectors() {
+    let mut sections = load();
+    check!(
+        sections,
+        decoder: Base64Mixed, Base64MixedNoPadding;
+        codec: Base64, Base64NoPadding, Base64UrlSafe, Base64UrlSafeNoPadding,
+            Base32, Base32NoPadding, Base32Hex, Base32HexNoPadding, Hex
+    );
+    assert!(
+        sections.is_empty(),
+        "sections without a type: {:?}",
+        sections.keys().collect::<Vec<_>>()
+    );
+}